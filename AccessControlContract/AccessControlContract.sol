@@ -1,6 +1,13 @@
 // SPDX-License-Identifier: MIT
 pragma solidity ^0.8.0;
 import "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
+
+// Groth16 verifier rendered by elgamal-proof's `solidity::render_verifier` for the
+// proof of correct encryption (ElGamalCorrectEncryptionCircuit over BN254)
+interface IEncryptionVerifier {
+    function verifyProof(uint256[8] calldata proof, uint256[] calldata input) external view returns (bool);
+}
+
 contract AccessControlContract {
     address public owner; // Address of the contract owner
     bytes32[] public blockIDS; // Array of hashed block IDs associated with the record
@@ -8,7 +15,11 @@ contract AccessControlContract {
     uint256 public refundWindow; // Time window for refund requests
     bytes public publicKey; // Buyer public key for encryption/decryption
     bytes32 public recordID; // Unique identifier for the record
-    bool public proofOfEncryption; // Placeholder for proof of encryption (assume true for now)
+    bool public proofOfEncryption; // True once every block has a verified proof of correct encryption
+    IEncryptionVerifier public encryptionVerifier; // Verifier contract, or address(0) to trust the seller
+    mapping(uint256 => bool) public blockEncryptionVerified; // Block index => proof accepted
+    mapping(uint256 => uint256[2]) public blockCiphertexts; // Block index => proven ciphertext (c1, c2)
+    uint256 public verifiedBlocks; // Number of blocks with an accepted proof
     mapping(address => mapping(uint256 => uint256[])) public access_table;
 
     struct Request {
@@ -18,12 +29,16 @@ contract AccessControlContract {
 
     mapping(address => Request) public requests; // Array of requests made by buyers
 
+    // Emitted once per block with the ciphertext its proof of encryption was accepted for
+    event BlockEncryptionProven(uint256 indexed blockIndex, uint256 c1, uint256 c2);
+
     constructor(
         bytes32[] memory _blockIDS,
         uint256 _recordFee,
         uint256 _refundWindow,
         bytes memory _publicKey,
-        bytes32 _recordID
+        bytes32 _recordID,
+        address _encryptionVerifier
     ) {
         owner = msg.sender;
         blockIDS = _blockIDS;
@@ -31,12 +46,59 @@ contract AccessControlContract {
         refundWindow = _refundWindow;
         publicKey = _publicKey;
         recordID = _recordID;
-        proofOfEncryption = true; // Placeholder for proof of encryption
+        encryptionVerifier = IEncryptionVerifier(_encryptionVerifier);
+        // Without a verifier the seller is trusted, as before proofs could be checked on-chain
+        proofOfEncryption = _encryptionVerifier == address(0);
+    }
+
+    // Function called by the seller to prove block `blockIndex` is an encryption of a message
+    // hashing to its block ID under the buyer's key `hek`; ciphertext = (c1, c2)
+    // The ciphertext is stored with the block, so the buyer can check that what the seller
+    // delivers is the ciphertext the proof was accepted for
+    function submitProofOfEncryption(
+        uint256 blockIndex,
+        uint256[8] calldata proof,
+        uint256 c1,
+        uint256 c2,
+        uint256 hek
+    ) external {
+        require(msg.sender == owner, "Only owner can submit proofs");
+        require(address(encryptionVerifier) != address(0), "No encryption verifier");
+        require(blockIndex < blockIDS.length, "Block index out of range");
+        require(!blockEncryptionVerified[blockIndex], "Block already proven");
+        require(publicKey.length != 0, "No buyer public key");
+        require(keccak256(publicKey) == keccak256(abi.encodePacked(hek)), "Proof is for another public key");
+
+        // Public inputs in circuit order: c1, c2, hek, then the block ID bytes packed
+        // little-endian into field elements of 31 bytes each
+        (uint256 bidLow, uint256 bidHigh) = packBlockID(blockIDS[blockIndex]);
+        uint256[] memory input = new uint256[](5);
+        input[0] = c1;
+        input[1] = c2;
+        input[2] = hek;
+        input[3] = bidLow;
+        input[4] = bidHigh;
+        require(encryptionVerifier.verifyProof(proof, input), "Invalid proof of encryption");
+
+        blockEncryptionVerified[blockIndex] = true;
+        blockCiphertexts[blockIndex] = [c1, c2];
+        verifiedBlocks++;
+        emit BlockEncryptionProven(blockIndex, c1, c2);
+        proofOfEncryption = verifiedBlocks == blockIDS.length;
+    }
+
+    // Packs a block ID the way the circuit allocates its bytes as public inputs
+    function packBlockID(bytes32 blockID) public pure returns (uint256 low, uint256 high) {
+        for (uint256 i = 0; i < 31; i++) {
+            low |= uint256(uint8(blockID[i])) << (8 * i);
+        }
+        high = uint256(uint8(blockID[31]));
     }
 
     // Function called by the buyer to request access to the record
     function requestRecordAccess(bytes32[] memory blockIDs) external payable {
     require(msg.value >= recordFee, "Insufficient fee");
+    require(proofOfEncryption, "Encryption not proven");
     require(keccak256(abi.encodePacked(blockIDs)) == recordID, "Invalid recordID/blockIDs");
     requests[msg.sender] = Request({
         requester_address: msg.sender,
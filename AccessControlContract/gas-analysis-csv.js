@@ -58,7 +58,7 @@ describe("AccessControlContract Gas Analysis", function () {
 
     beforeEach(async function () {
       // Deploy contract
-      contract = await contractFactory.deploy(blockIDS, recordFee, refundWindow, "0x", recordID);
+      contract = await contractFactory.deploy(blockIDS, recordFee, refundWindow, "0x", recordID, ethers.ZeroAddress);
       await contract.waitForDeployment();
       
       // Get deployment transaction receipt and log constructor gas
@@ -137,7 +137,7 @@ describe("AccessControlContract Gas Analysis", function () {
         const recordID = calculateRecordID(blockIDS);
         
         // Deploy contract
-        const contract = await contractFactory.deploy(blockIDS, recordFee, refundWindow, "0x", recordID);
+        const contract = await contractFactory.deploy(blockIDS, recordFee, refundWindow, "0x", recordID, ethers.ZeroAddress);
         await contract.waitForDeployment();
         
         // Log constructor gas
@@ -31,7 +31,7 @@ async function main() {
   fs.appendFileSync(PARAMS_PATH, "=== Constructor ===\n");
   fs.appendFileSync(
     PARAMS_PATH,
-    `blockIDS: ${arrayLiteral(blockIDS)}\nrecordFee: ${recordFee.toString()}\nrefundWindow: ${refundWindow}\npublicKey: "${publicKey}"\nrecordID: "${recordID}"\n\n`
+    `blockIDS: ${arrayLiteral(blockIDS)}\nrecordFee: ${recordFee.toString()}\nrefundWindow: ${refundWindow}\npublicKey: "${publicKey}"\nrecordID: "${recordID}"\nencryptionVerifier: "${ethers.ZeroAddress}"\n\n`
   );
 
   // --- requestRecordAccess ---
@@ -0,0 +1,118 @@
+//! Constraint counts, synthesis and proving time of each gadget in the ABE circuit
+//!
+//! Every gadget is synthesized on its own over BLS12-377's base field, with its inputs
+//! allocated as witnesses, and proven with Groth16 over BW6-761. Run with
+//! `cargo bench --bench gadgets [-- <filter>]`; the counts are printed before each group's timings.
+
+use ark_bls12_377::{Config as BLSConfig, Fq, Fq12Config, Fr, G2Affine};
+use ark_bw6_761::BW6_761;
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::r1cs_to_qap::LibsnarkReduction;
+use ark_groth16::Groth16;
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    convert::ToBitsGadget,
+    fields::{emulated_fp::EmulatedFpVar, fp12::Fp12Var, FieldVar},
+    groups::{
+        bls12::{G1Var, G2Var},
+        CurveVar,
+    },
+    pairing::{bls12::PairingVar as BLS12PairingVar, PairingVar},
+    prelude::Boolean,
+    uint8::UInt8,
+};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use circuit_snapshot::{CircuitCounts, GadgetCircuit};
+use criterion::{criterion_group, criterion_main, Criterion};
+use hash_gadgets::BlockIdHash;
+use abe_proof::sample_circuit;
+
+type Fq12Var = Fp12Var<Fq12Config>;
+
+/// Prints the size of `circuit`, then benchmarks its synthesis and its Groth16 proof
+fn bench_circuit<C: ConstraintSynthesizer<Fq> + Clone>(c: &mut Criterion, name: &str, circuit: C) {
+    let counts = CircuitCounts::of::<Fq, _>(name, circuit.clone());
+    println!("{}: {} constraints, {} witnesses", name, counts.constraints, counts.witnesses);
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    let mut rng = StdRng::seed_from_u64(24);
+    // Criterion only runs the routine when the benchmark matches the filter, so setup stays lazy
+    let mut pk = None;
+    group.bench_function("synthesize", |b| b.iter(|| CircuitCounts::of::<Fq, _>(name, circuit.clone())));
+    group.bench_function("prove", |b| {
+        let pk = pk.get_or_insert_with(|| Groth16::<BW6_761, LibsnarkReduction>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap().0);
+        b.iter(|| Groth16::<BW6_761, LibsnarkReduction>::prove(pk, circuit.clone(), &mut rng).unwrap())
+    });
+    group.finish();
+}
+
+/// Allocates the bits of a scalar as witnesses, without the cost of decomposing an emulated field element
+fn scalar_bits(cs: ConstraintSystemRef<Fq>, scalar: Fr) -> Result<Vec<Boolean<Fq>>, SynthesisError> {
+    Vec::<Boolean<Fq>>::new_witness(cs, || {
+        Ok(scalar.into_bigint().to_bits_le()[..Fr::MODULUS_BIT_SIZE as usize].to_vec())
+    })
+}
+
+fn abe_gadgets(c: &mut Criterion) {
+    let circuit = sample_circuit(BlockIdHash::Sha256);
+    let (k, t) = (circuit.k, circuit.t);
+    let (attr_hash_point, ct4) = (circuit.attr_hash_point, circuit.ct4);
+    let (pk0, pk1) = (circuit.pk0, circuit.pk1);
+
+    bench_circuit(c, "emulated_to_bits_le", GadgetCircuit(move |cs: ConstraintSystemRef<Fq>| {
+        let t_var = EmulatedFpVar::<Fr, Fq>::new_witness(cs, || Ok(t))?;
+        t_var.to_bits_le()?;
+        Ok(())
+    }));
+
+    // The block ID hashes of k's bytes; `block_id_sha256` is the bare `Sha256Gadget`
+    let k_bytes: [u8; 32] = k.into_bigint().to_bytes_le().try_into().unwrap();
+    for hash in [BlockIdHash::Sha256, BlockIdHash::Keccak256, BlockIdHash::Poseidon] {
+        let name = format!("block_id_{:?}", hash).to_lowercase();
+        bench_circuit(c, &name, GadgetCircuit(move |cs: ConstraintSystemRef<Fq>| {
+            let bytes = UInt8::new_witness_vec(cs, &k_bytes)?;
+            hash.block_id_gadget(&bytes)?;
+            Ok(())
+        }));
+    }
+
+    bench_circuit(c, "g1_scalar_mul_le", GadgetCircuit(move |cs: ConstraintSystemRef<Fq>| {
+        let base = G1Var::<BLSConfig>::new_witness(cs.clone(), || Ok(attr_hash_point))?;
+        let bits = scalar_bits(cs, t)?;
+        let _ = base.scalar_mul_le(bits.iter())?;
+        Ok(())
+    }));
+
+    bench_circuit(c, "emulated_pow_le", GadgetCircuit(move |cs: ConstraintSystemRef<Fq>| {
+        let base = EmulatedFpVar::<Fr, Fq>::new_witness(cs.clone(), || Ok(pk1))?;
+        let bits = scalar_bits(cs, t)?;
+        let _ = base.pow_le(&bits)?;
+        Ok(())
+    }));
+
+    // Pairing with the constant generator of G2, as in the ct4 check
+    bench_circuit(c, "pairing", GadgetCircuit(move |cs: ConstraintSystemRef<Fq>| {
+        let p = G1Var::<BLSConfig>::new_witness(cs.clone(), || Ok(ct4))?;
+        let q = G2Var::<BLSConfig>::new_constant(cs, G2Affine::generator())?;
+        let p_prepared = BLS12PairingVar::<BLSConfig>::prepare_g1(&p)?;
+        let q_prepared = BLS12PairingVar::<BLSConfig>::prepare_g2(&q)?;
+        let _ = BLS12PairingVar::<BLSConfig>::pairing(p_prepared, q_prepared)?;
+        Ok(())
+    }));
+
+    bench_circuit(c, "fq12_pow_le", GadgetCircuit(move |cs: ConstraintSystemRef<Fq>| {
+        let base = Fq12Var::new_witness(cs.clone(), || Ok(pk0))?;
+        let bits = scalar_bits(cs, t)?;
+        let _ = base.pow_le(&bits)?;
+        Ok(())
+    }));
+
+    bench_circuit(c, "abe_circuit", circuit);
+}
+
+criterion_group!(benches, abe_gadgets);
+criterion_main!(benches);
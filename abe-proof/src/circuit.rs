@@ -11,14 +11,12 @@ use ark_r1cs_std::convert::ToBitsGadget;
 use ark_r1cs_std::fields::FieldVar;
 use ark_r1cs_std::pairing::PairingVar;
 use std::ops::Mul;
-use ark_crypto_primitives::crh::CRHSchemeGadget;
-use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
-use ark_crypto_primitives::crh::sha256::constraints::UnitVar;
 use ark_r1cs_std::prelude::UInt8;
 use ark_r1cs_std::prelude::Boolean;
 use ark_r1cs_std::eq::EqGadget;
 use ark_r1cs_std::groups::CurveVar;
-use ark_ff::{Field, PrimeField};
+use ark_ff::PrimeField;
+use hash_gadgets::BlockIdHash;
 
 // For BLS12-377, we need to use the proper Fq12 field configuration
 type Fq12Var = Fp12Var<ark_bls12_377::Fq12Config>;
@@ -28,6 +26,7 @@ pub struct PairingCircuit {
     pub s: Fr,              // Private witness 
     pub k: Fr,              // Private scalar that gets hashed to bid
     pub bid: [u8; 32],      // block id is the hash of k
+    pub hash: BlockIdHash,  // Hash function deriving bid from k
     pub attr_hash_point: G1Affine, // Attribute hash point in G1
 
     pub ct0: Fq12,          // First element of ciphertext Ct[0] - this is in GT (target group)
@@ -64,7 +63,7 @@ impl ConstraintSynthesizer<Fq> for PairingCircuit {
         let attr_base_var = G1Var::<BLSConfig>::new_input(cs.clone(), || Ok(self.attr_hash_point))?;
         let pk0_var = Fq12Var::new_input(cs.clone(), || Ok(self.pk0))?;
         let pk1_var = EmulatedFpVar::<Fr,Fq>::new_input(cs.clone(), || Ok(self.pk1))?;
-        let bid_var = UInt8::<Fq>::new_input_vec(cs.clone(), &self.bid.to_vec())?;
+        let bid_var = UInt8::<Fq>::new_input_vec(cs.clone(), &self.bid)?;
                 
         // === Constants (Prepare once, reuse multiple times) ===
         let g1_gen = G1Var::<BLSConfig>::new_constant(
@@ -100,15 +99,14 @@ impl ConstraintSynthesizer<Fq> for PairingCircuit {
         }
         
         let k_bytes: Vec<UInt8<Fq>> = padded_bits.chunks_exact(8)
-            .map(|chunk| UInt8::from_bits_le(chunk))
+            .map(UInt8::from_bits_le)
             .collect();
 
-        // Compute SHA256(k_bytes) and compare with bid
-        let params_var = UnitVar::default();
-        let hash_k = Sha256Gadget::evaluate(&params_var, &k_bytes)?;
+        // Compute H(k_bytes) and compare with bid
+        let hash_k = self.hash.block_id_gadget(&k_bytes)?;
 
         // Batch equality check
-        hash_k.0.enforce_equal(&bid_var)?;
+        hash_k.enforce_equal(&bid_var)?;
 
         // === Verify ct0 = e(g,g)^(k+s) ===
         // Compute k + s
@@ -158,6 +156,153 @@ impl ConstraintSynthesizer<Fq> for PairingCircuit {
 
         let lhs = BLS12PairingVar::<BLSConfig>::pairing(ct4_prepared, g2_prepared.clone())?;
         let rhs = BLS12PairingVar::<BLSConfig>::pairing(expected_prepared, g2_prepared)?;
+        lhs.enforce_equal(&rhs)?;
+
         Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute_block_id_hash_from_scalar, sample_circuit};
+    use ark_bls12_377::Bls12_377;
+    use ark_ec::{pairing::Pairing, CurveGroup};
+    use ark_ff::Field;
+    use ark_relations::r1cs::ConstraintSystem;
+    use hash_gadgets::BlockIdHash;
+    use proptest::prelude::*;
+
+    fn is_satisfied(circuit: PairingCircuit) -> bool {
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    /// e(g1, g2)^delta, so a tampered GT element stays in the target group
+    fn gt_power(delta: u64) -> Fq12 {
+        Bls12_377::pairing(G1Affine::generator(), G2Affine::generator()).0.pow([delta])
+    }
+
+    /// g1^delta, so a tampered G1 element stays in the prime-order subgroup
+    fn g1_power(delta: u64) -> G1Affine {
+        (G1Affine::generator() * Fr::from(delta)).into_affine()
+    }
+
+    #[test]
+    fn test_sample_circuit_is_satisfied() {
+        assert!(is_satisfied(sample_circuit(BlockIdHash::Sha256)));
+    }
+
+    // Each case synthesizes the whole circuit (about 600k constraints), so keep the case count low
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+
+        #[test]
+        fn prop_rejects_tampered_ct0(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.ct0 *= gt_power(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_ct1(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.ct1 *= gt_power(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_ct2(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.ct2 = (circuit.ct2 + g1_power(delta)).into_affine();
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_ct3(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.ct3 += Fr::from(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_ct4(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.ct4 = (circuit.ct4 + g1_power(delta)).into_affine();
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_pk0(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.pk0 *= gt_power(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_pk1(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.pk1 += Fr::from(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_s(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.s += Fr::from(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_k(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.k += Fr::from(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        /// A k that matches the block ID must still open ct0
+        #[test]
+        fn prop_rejects_tampered_k_with_matching_bid(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.k += Fr::from(delta);
+            circuit.bid = compute_block_id_hash_from_scalar(circuit.k, circuit.hash);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_lambda(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.lambda += Fr::from(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_t(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.t += Fr::from(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_w(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.w += Fr::from(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_attr_hash_point(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.attr_hash_point = (circuit.attr_hash_point + g1_power(delta)).into_affine();
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_block_id(index in 0..32usize, flip in 1..=u8::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.bid[index] ^= flip;
+            prop_assert!(!is_satisfied(circuit));
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,58 @@
+pub mod circuit;
+
+use ark_bls12_377::{Bls12_377, Fq, Fr, G1Affine, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup, pairing::Pairing};
+use ark_ff::{Field, PrimeField};
+use hash_gadgets::BlockIdHash;
+
+use crate::circuit::PairingCircuit;
+
+fn compute_block_id_hash_from_scalar(k: Fr, hash: BlockIdHash) -> [u8; 32] {
+    // Hash the 32 little-endian bytes of the Fr scalar (big-endian for Keccak-256);
+    // Poseidon runs over the circuit field Fq
+    hash.block_id_in::<Fq, _>(&k)
+}
+
+/// Builds the demo ABE circuit from fixed key, ciphertext and attribute values
+pub fn sample_circuit(hash: BlockIdHash) -> PairingCircuit {
+    let s = Fr::from(3u8);
+    let lambda = Fr::from(3u8);
+    let t = Fr::from(3u8);
+    let w = Fr::from(3u8);
+    let g1_gen = G1Affine::generator();
+    let g2_gen = G2Affine::generator();
+    let g = Fr::from(2u64);
+    let attr_hash_point = (g1_gen * Fr::from(3u8)).into_affine();
+    let alpha = Fr::from(3u8);
+    let y = Fr::from(3u8);
+    let pk0 = Bls12_377::pairing(g1_gen, g2_gen).0.pow(alpha.into_bigint());
+    let pk1 = g.pow(y.into_bigint());
+    let k_scalar = Fr::from(3u8);
+    let base_pairing = Bls12_377::pairing(g1_gen, g2_gen);
+    let k_plus_s = k_scalar + s;
+    let ct0 = base_pairing.0.pow(k_plus_s.into_bigint());
+    let bid = compute_block_id_hash_from_scalar(k_scalar, hash);
+    let ct1 = base_pairing.0.pow(lambda.into_bigint()) * pk0.pow(t.into_bigint());
+    let neg_t = -t;
+    let ct2 = (g1_gen * neg_t).into_affine();
+    let ct3 = pk1.pow(t.into_bigint()) * g.pow(w.into_bigint());
+    let ct4 = (attr_hash_point * t).into_affine();
+
+    PairingCircuit {
+        s,
+        k: k_scalar,
+        bid,
+        hash,
+        attr_hash_point,
+        ct0,
+        ct1,
+        ct2,
+        ct3,
+        ct4,
+        lambda,
+        t,
+        w,
+        pk0,
+        pk1,
+    }
+}
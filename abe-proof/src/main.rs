@@ -1,33 +1,55 @@
-use ark_bls12_377::{Config as BLSConfig, Fq, Fr, G1Affine, G2Affine, Fq12, Bls12_377};
+use ark_bls12_377::{Fq, Fq12, Fr, G1Affine};
 use ark_bw6_761::{BW6_761, Fr as BW6Fr};
-use ark_ec::{AffineRepr, CurveGroup, pairing::Pairing};
-use ark_ff::{Field, ToConstraintField, PrimeField, UniformRand};
-use ark_groth16::{Groth16, Proof};
+use ark_ff::PrimeField;
+use ark_groth16::Groth16;
 use ark_relations::r1cs::ConstraintSystem;
 use ark_snark::SNARK;
 use ark_groth16::r1cs_to_qap::LibsnarkReduction;
-use ark_std::rand::thread_rng;
-use sha2::{Sha256, Digest};
-use circuit::PairingCircuit;
+use ark_std::rand::{rngs::StdRng, thread_rng, SeedableRng};
+use ark_serialize::CanonicalSerialize;
+use bench_report::{BenchmarkOptions, BenchmarkReport, Iteration};
+use abe_proof::{circuit::PairingCircuit, sample_circuit};
 use std::time::Instant;
 use ark_relations::r1cs::ConstraintSynthesizer;
 use ark_ff::BigInteger;
-use ark_ff::BigInt;
-use std::ops::Mul;
-use std::time::Duration;
-mod circuit;
-
-
-
-
+use hash_gadgets::BlockIdHash;
+use key_store::{
+    key_dir, load_proof, load_proving_key, load_verifying_key, load_witness, save_keys, save_proof, save_witness,
+    PROOF_FILE, PROVING_KEY_FILE, PUBLIC_INPUTS_FILE, VERIFYING_KEY_FILE, WITNESS_FILE,
+};
+
+/// Inputs of a block as stored in `witness.bin`: the scalars (s, k, λ, t, w), the ciphertext
+/// (ct0, ct1, ct2, ct3, ct4) and the public inputs (attr_hash_point, pk0, pk1, bid)
+type AbeWitness = ((Fr, Fr, Fr, Fr, Fr), (Fq12, Fq12, G1Affine, Fr, G1Affine), (G1Affine, Fq12, Fr, [u8; 32]));
+
+fn circuit_witness(c: &PairingCircuit) -> AbeWitness {
+    (
+        (c.s, c.k, c.lambda, c.t, c.w),
+        (c.ct0, c.ct1, c.ct2, c.ct3, c.ct4),
+        (c.attr_hash_point, c.pk0, c.pk1, c.bid),
+    )
+}
 
+fn witness_circuit(witness: AbeWitness, hash: BlockIdHash) -> PairingCircuit {
+    let ((s, k, lambda, t, w), (ct0, ct1, ct2, ct3, ct4), (attr_hash_point, pk0, pk1, bid)) = witness;
+    PairingCircuit { s, k, bid, hash, attr_hash_point, ct0, ct1, ct2, ct3, ct4, lambda, t, w, pk0, pk1 }
+}
 
-fn compute_block_id_hash_from_scalar(k: Fr) -> [u8; 32] {
-    // Convert Fr scalar to bytes for hashing
-    let k_bytes = k.into_bigint().to_bytes_le();
-    let mut hasher = Sha256::new();
-    hasher.update(&k_bytes);
-    hasher.finalize().into()
+/// Prints the change from the SHA-256 baseline as an absolute and relative delta
+fn print_hash_deltas(hash: BlockIdHash, report: &BenchmarkReport, baseline: &BenchmarkReport) {
+    let percent = |new: f64, old: f64| (new - old) / old * 100.0;
+
+    println!("\n⚖️  {:?} vs Sha256:", hash);
+    println!("   🧮 Constraints: {} vs {} ({:+} / {:+.1}%)",
+             report.constraints, baseline.constraints,
+             report.constraints as i64 - baseline.constraints as i64,
+             percent(report.constraints as f64, baseline.constraints as f64));
+    println!("   🔧 Avg Setup Time:   {:.2} ms vs {:.2} ms ({:+.1} ms / {:+.1}%)",
+             report.setup.mean_ms, baseline.setup.mean_ms,
+             report.setup.mean_ms - baseline.setup.mean_ms, percent(report.setup.mean_ms, baseline.setup.mean_ms));
+    println!("   🎯 Avg Proving Time: {:.2} ms vs {:.2} ms ({:+.1} ms / {:+.1}%)",
+             report.prove.mean_ms, baseline.prove.mean_ms,
+             report.prove.mean_ms - baseline.prove.mean_ms, percent(report.prove.mean_ms, baseline.prove.mean_ms));
 }
 
 fn fq_to_bw6fr(fq: Fq) -> BW6Fr {
@@ -36,60 +58,123 @@ fn fq_to_bw6fr(fq: Fq) -> BW6Fr {
 }
 
 fn main() {
-    let mut rng = thread_rng();
     println!("🚀 Starting Pairing Circuit ZK-SNARK Demo");
 
-    // === Constants ===
-    let iterations = 10;
-    let mut total_setup = Duration::ZERO;
-    let mut total_proving = Duration::ZERO;
-    let mut total_verifying = Duration::ZERO;
+    // An optional leading `setup`, `witness`, `prove` or `verify` works on the key files instead of benchmarking
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // `--iterations <n>`, `--seed <seed>` and `--out <dir>` may appear anywhere and configure the benchmark report
+    let options = BenchmarkOptions::from_args(&mut args);
+
+    let command = match args.first().map(String::as_str) {
+        Some("setup" | "witness" | "prove" | "verify") => Some(args.remove(0)),
+        _ => None,
+    };
 
+    // Select the block ID hash with the first argument: `sha256` (default), `keccak` or `poseidon`
+    let hash = match args.first().map(String::as_str) {
+        None | Some("sha256") => BlockIdHash::Sha256,
+        Some("keccak") => BlockIdHash::Keccak256,
+        Some("poseidon") => BlockIdHash::Poseidon,
+        Some(other) => panic!("Unknown block ID hash `{}` (expected `sha256`, `keccak` or `poseidon`)", other),
+    };
+    println!("#️⃣  Block ID hash: {:?}", hash);
+
+    if let Some(command) = command {
+        return run_key_command(&command, hash);
+    }
+
+    let report = run_benchmark(hash, &options);
+
+    // Other hashes are always reported against the SHA-256 baseline
+    if hash != BlockIdHash::Sha256 {
+        println!("\n🔁 Re-running with Sha256 block IDs for comparison");
+        let baseline = run_benchmark(BlockIdHash::Sha256, &options);
+        print_hash_deltas(hash, &report, &baseline);
+    }
+}
+
+/// Public inputs of `circuit`, mapped from the circuit field Fq into BW6-761's scalar field
+fn bw6_public_inputs(circuit: &PairingCircuit) -> Vec<BW6Fr> {
+    let cs = ConstraintSystem::<Fq>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    let instance_assignment = cs.borrow().unwrap().instance_assignment.clone();
+    instance_assignment.iter().skip(1).map(|fq_elem| fq_to_bw6fr(*fq_elem)).collect()
+}
+
+/// `setup` writes fresh keys, `witness` writes the sample block, `prove` proves the block in
+/// the witness file with the stored proving key and `verify` checks the stored proof with the
+/// stored verifying key
+fn run_key_command(command: &str, hash: BlockIdHash) {
+    let dir = key_dir(&format!("abe-{:?}", hash).to_lowercase());
+    let mut rng = thread_rng();
+    match command {
+        "setup" => {
+            let setup_start = Instant::now();
+            let (pk, vk) = Groth16::<BW6_761, LibsnarkReduction>::circuit_specific_setup(sample_circuit(hash), &mut rng).unwrap();
+            println!("🔧 Setup Time: {:.2?}", setup_start.elapsed());
+            save_keys(&dir, &pk, &vk).expect("Failed to write keys");
+            println!("🔑 Wrote {} and {} to {}", PROVING_KEY_FILE, VERIFYING_KEY_FILE, dir.display());
+        }
+        "witness" => {
+            save_witness(&dir, &circuit_witness(&sample_circuit(hash))).expect("Failed to write witness");
+            println!("🧾 Wrote the sample block to {} in {}", WITNESS_FILE, dir.display());
+        }
+        "prove" => {
+            let pk = load_proving_key::<BW6_761>(&dir).expect("Failed to read proving key; run `setup` first");
+            let witness = load_witness::<AbeWitness>(&dir).unwrap_or_else(|err| {
+                panic!("Failed to read {} in {} ({}); write the block to prove there first", WITNESS_FILE, dir.display(), err)
+            });
+            let circuit = witness_circuit(witness, hash);
+
+            // Groth16 would still output a proof for a wrong witness, it just would not verify.
+            // The check's constraint system is dropped before proving, which builds its own.
+            let is_satisfied = {
+                let cs = ConstraintSystem::<Fq>::new_ref();
+                circuit.clone().generate_constraints(cs.clone()).expect("Failed to synthesize the circuit");
+                cs.is_satisfied().unwrap()
+            };
+            if !is_satisfied {
+                println!("❌ The block in {} does not satisfy the circuit", WITNESS_FILE);
+                std::process::exit(1);
+            }
+
+            let proving_start = Instant::now();
+            let proof = Groth16::<BW6_761, LibsnarkReduction>::prove(&pk, circuit.clone(), &mut rng).unwrap();
+            println!("🎯 Proving Time: {:.2?}", proving_start.elapsed());
+            save_proof(&dir, &proof, &bw6_public_inputs(&circuit)).expect("Failed to write proof");
+            println!("📄 Wrote {} and {} to {}", PROOF_FILE, PUBLIC_INPUTS_FILE, dir.display());
+        }
+        "verify" => {
+            let vk = load_verifying_key::<BW6_761>(&dir).expect("Failed to read verifying key; run `setup` first");
+            let (proof, public_inputs) = load_proof::<BW6_761>(&dir).expect("Failed to read proof; run `prove` first");
+            let verification_start = Instant::now();
+            let is_valid = Groth16::<BW6_761, LibsnarkReduction>::verify(&vk, &public_inputs, &proof).unwrap();
+            println!("🔍 Verification Time: {:.2?}", verification_start.elapsed());
+            if !is_valid {
+                println!("❌ Proof in {} is invalid", dir.display());
+                std::process::exit(1);
+            }
+            println!("✅ Proof in {} is valid", dir.display());
+        }
+        other => unreachable!("unknown key command `{}`", other),
+    }
+}
+
+/// Runs the benchmark iterations with the given block ID hash, prints statistics and writes them
+/// to the report directory as `abe-proof-abe-<hash>.csv` and `.json`
+fn run_benchmark(hash: BlockIdHash, options: &BenchmarkOptions) -> BenchmarkReport {
     // === Shared inputs ===
-    let s = Fr::from(3u8);
-    let lambda = Fr::from(3u8);
-    let t = Fr::from(3u8);
-    let w = Fr::from(3u8);
-    let g1_gen = G1Affine::generator();
-    let g2_gen = G2Affine::generator();
-    let g = Fr::from(2u64);
-    let attr_hash_point = (g1_gen * Fr::from(3u8)).into_affine();
-    let alpha = Fr::from(3u8);
-    let y = Fr::from(3u8);
-    let pk0 = Bls12_377::pairing(g1_gen, g2_gen).0.pow(alpha.into_bigint());
-    let pk1 = g.pow(y.into_bigint());
-    let k_scalar = Fr::from(3u8);
-    let base_pairing = Bls12_377::pairing(g1_gen, g2_gen);
-    let k_plus_s = k_scalar + s;
-    let ct0 = base_pairing.0.pow(k_plus_s.into_bigint());
-    let bid = compute_block_id_hash_from_scalar(k_scalar);
-    let ct1 = base_pairing.0.pow(lambda.into_bigint()) * pk0.pow(t.into_bigint());
-    let neg_t = -t;
-    let ct2 = (g1_gen * neg_t).into_affine();
-    let ct3 = pk1.pow(t.into_bigint()) * g.pow(w.into_bigint());
-    let ct4 = (attr_hash_point * t).into_affine();
-
-    println!("\n🚀 Running {} iterations for benchmark...\n", iterations);
-
-    for i in 0..iterations {
-        println!("🔁 Iteration {}/{}", i + 1, iterations);
-
-        let circuit = PairingCircuit {
-            s,
-            k: k_scalar,
-            bid,
-            attr_hash_point,
-            ct0,
-            ct1,
-            ct2,
-            ct3,
-            ct4,
-            lambda,
-            t,
-            w,
-            pk0,
-            pk1,
-        };
+    let sample = sample_circuit(hash);
+    let mut iterations = Vec::new();
+
+    println!("\n🚀 Running {} iterations for benchmark...\n", options.iterations);
+
+    for i in 0..options.iterations {
+        println!("🔁 Iteration {}/{}", i + 1, options.iterations);
+
+        let mut rng = StdRng::seed_from_u64(options.seed.wrapping_add(i as u64));
+        let circuit = sample.clone();
 
         // Constraint system (for public inputs)
         let cs = ConstraintSystem::<Fq>::new_ref();
@@ -103,36 +188,62 @@ fn main() {
         let setup_start = Instant::now();
         let (pk, vk) = Groth16::<BW6_761, LibsnarkReduction>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
         let setup_time = setup_start.elapsed();
-        total_setup += setup_time;
 
         // === Prove ===
         let proving_start = Instant::now();
         let proof = Groth16::<BW6_761, LibsnarkReduction>::prove(&pk, circuit.clone(), &mut rng).unwrap();
         let proving_time = proving_start.elapsed();
-        total_proving += proving_time;
 
         // === Public Inputs ===
-        let instance_assignment = cs.borrow().unwrap().instance_assignment.clone();
-        let public_inputs: Vec<BW6Fr> = instance_assignment.iter().skip(1)
-            .map(|fq_elem| fq_to_bw6fr(*fq_elem))
-            .collect();
+        let public_inputs = bw6_public_inputs(&circuit);
 
         // === Verify ===
         let verification_start = Instant::now();
         let is_valid = Groth16::<BW6_761, LibsnarkReduction>::verify(&vk, &public_inputs, &proof).unwrap();
         let verification_time = verification_start.elapsed();
-        total_verifying += verification_time;
 
         if is_valid {
             println!("✅ Passed (Setup: {:?}, Prove: {:?}, Verify: {:?})", setup_time, proving_time, verification_time);
         } else {
             println!("❌ Failed verification in iteration {}", i + 1);
         }
+
+        iterations.push(Iteration {
+            constraints: cs.num_constraints(),
+            witnesses: cs.num_witness_variables(),
+            proof_bytes: proof.compressed_size(),
+            setup: setup_time,
+            prove: proving_time,
+            verify: verification_time,
+        });
     }
 
-    // === Average Results ===
-    println!("\n📊 Average Benchmark Results over {} iterations:", iterations);
-    println!("   🔧 Avg Setup Time:        {:.2?}", total_setup / iterations);
-    println!("   🎯 Avg Proving Time:      {:.2?}", total_proving / iterations);
-    println!("   🔍 Avg Verification Time: {:.2?}", total_verifying / iterations);
+    // === Results ===
+    let name = format!("abe-{:?}", hash).to_lowercase();
+    let report = BenchmarkReport::new("abe-proof", &name, options, &iterations);
+    report.print();
+    let (csv_path, json_path) = report.write(&options.out_dir).expect("Failed to write benchmark report");
+    println!("\n📝 Wrote {} and {}", csv_path.display(), json_path.display());
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use circuit_snapshot::{check_snapshot, CircuitCounts};
+
+    /// Counts for the ABE circuit, checked against `constraint-counts.snapshot`
+    #[test]
+    fn test_constraint_count_snapshot() {
+        let counts: Vec<CircuitCounts> = [
+            ("abe_sha256", BlockIdHash::Sha256),
+            ("abe_keccak256", BlockIdHash::Keccak256),
+            ("abe_poseidon", BlockIdHash::Poseidon),
+        ]
+        .into_iter()
+        .map(|(name, hash)| CircuitCounts::of::<Fq, _>(name, sample_circuit(hash)))
+        .collect();
+
+        check_snapshot(concat!(env!("CARGO_MANIFEST_DIR"), "/constraint-counts.snapshot"), &counts);
+    }
 }
@@ -0,0 +1,316 @@
+//! Benchmark statistics and CSV/JSON reports shared by the proof binaries
+//!
+//! Each binary records the setup, prove and verify time of every iteration, summarises them
+//! in a `BenchmarkReport` and writes it to `<out>/<binary>-<variant>.csv` and `.json`.
+//! The command line controls the run:
+//!
+//! | Flag               | Default   |
+//! |--------------------|-----------|
+//! | `--iterations <n>` | 10        |
+//! | `--seed <seed>`    | 12345     |
+//! | `--out <dir>`      | `reports` |
+//!
+//! The CSV holds one summary row with every time in milliseconds, ready to paste into a
+//! spreadsheet; the JSON also keeps the raw samples.
+
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_ITERATIONS: usize = 10;
+pub const DEFAULT_SEED: u64 = 12345;
+pub const DEFAULT_OUT_DIR: &str = "reports";
+
+/// Benchmark settings taken from the command line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BenchmarkOptions {
+    pub iterations: usize,
+    pub seed: u64,
+    pub out_dir: PathBuf,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        BenchmarkOptions { iterations: DEFAULT_ITERATIONS, seed: DEFAULT_SEED, out_dir: PathBuf::from(DEFAULT_OUT_DIR) }
+    }
+}
+
+impl BenchmarkOptions {
+    /// Removes `--iterations`, `--seed` and `--out` with their values from `args`
+    /// The remaining positional arguments keep their order, so binaries parse them as before.
+    pub fn from_args(args: &mut Vec<String>) -> Self {
+        let mut options = Self::default();
+        let mut i = 0;
+        while i < args.len() {
+            let flag = args[i].clone();
+            if !matches!(flag.as_str(), "--iterations" | "--seed" | "--out") {
+                i += 1;
+                continue;
+            }
+            let value = args.get(i + 1).cloned().unwrap_or_else(|| panic!("Missing value for `{}`", flag));
+            match flag.as_str() {
+                "--iterations" => {
+                    options.iterations = value
+                        .parse()
+                        .ok()
+                        .filter(|iterations| *iterations > 0)
+                        .unwrap_or_else(|| panic!("Invalid iteration count `{}`", value));
+                }
+                "--seed" => options.seed = value.parse().unwrap_or_else(|_| panic!("Invalid seed `{}`", value)),
+                _ => options.out_dir = PathBuf::from(value),
+            }
+            args.drain(i..i + 2);
+        }
+        options
+    }
+}
+
+/// Measurements of one benchmark iteration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Iteration {
+    pub constraints: usize,
+    pub witnesses: usize,
+    pub proof_bytes: usize,
+    pub setup: Duration,
+    pub prove: Duration,
+    pub verify: Duration,
+}
+
+/// Summary of one phase's timings in milliseconds
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TimingStats {
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub std_dev_ms: f64,
+    pub p95_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub samples_ms: Vec<f64>,
+}
+
+impl TimingStats {
+    /// Statistics of `samples`, kept in the order they were taken
+    /// The standard deviation is the sample (n - 1) one and p95 uses the nearest-rank method,
+    /// so with 10 iterations it is the slowest run.
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        assert!(!samples.is_empty(), "statistics need at least one sample");
+        let samples_ms: Vec<f64> = samples.iter().map(|sample| sample.as_secs_f64() * 1000.0).collect();
+        let mut sorted = samples_ms.clone();
+        sorted.sort_by(f64::total_cmp);
+
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let median = if n % 2 == 1 { sorted[n / 2] } else { (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0 };
+        let variance = match n {
+            1 => 0.0,
+            _ => sorted.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / (n - 1) as f64,
+        };
+
+        TimingStats {
+            mean_ms: mean,
+            median_ms: median,
+            std_dev_ms: variance.sqrt(),
+            p95_ms: sorted[(n * 95).div_ceil(100) - 1],
+            min_ms: sorted[0],
+            max_ms: sorted[n - 1],
+            samples_ms,
+        }
+    }
+
+    fn csv_fields(&self) -> [f64; 6] {
+        [self.mean_ms, self.median_ms, self.std_dev_ms, self.p95_ms, self.min_ms, self.max_ms]
+    }
+}
+
+/// Results of benchmarking one circuit variant
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BenchmarkReport {
+    pub binary: String,
+    pub variant: String,
+    pub iterations: usize,
+    pub seed: u64,
+    pub constraints: usize,
+    pub witnesses: usize,
+    pub proof_bytes: usize,
+    pub setup: TimingStats,
+    pub prove: TimingStats,
+    pub verify: TimingStats,
+}
+
+impl BenchmarkReport {
+    /// Summarises the iterations of `binary`'s `variant`
+    /// Every iteration proves the same circuit shape, so the counts come from the first one.
+    pub fn new(binary: &str, variant: &str, options: &BenchmarkOptions, iterations: &[Iteration]) -> Self {
+        assert!(!iterations.is_empty(), "a report needs at least one iteration");
+        let phase = |time: fn(&Iteration) -> Duration| {
+            TimingStats::from_samples(&iterations.iter().map(time).collect::<Vec<_>>())
+        };
+        BenchmarkReport {
+            binary: binary.to_string(),
+            variant: variant.to_string(),
+            iterations: iterations.len(),
+            seed: options.seed,
+            constraints: iterations[0].constraints,
+            witnesses: iterations[0].witnesses,
+            proof_bytes: iterations[0].proof_bytes,
+            setup: phase(|iteration| iteration.setup),
+            prove: phase(|iteration| iteration.prove),
+            verify: phase(|iteration| iteration.verify),
+        }
+    }
+
+    /// Header and summary row, times in milliseconds
+    pub fn to_csv(&self) -> String {
+        let mut header = String::from("binary,variant,iterations,seed,constraints,witnesses,proof_bytes");
+        for phase in ["setup", "prove", "verify"] {
+            for stat in ["mean", "median", "std_dev", "p95", "min", "max"] {
+                write!(header, ",{}_{}_ms", phase, stat).unwrap();
+            }
+        }
+
+        let mut row = format!(
+            "{},{},{},{},{},{},{}",
+            csv_field(&self.binary),
+            csv_field(&self.variant),
+            self.iterations,
+            self.seed,
+            self.constraints,
+            self.witnesses,
+            self.proof_bytes
+        );
+        for stats in [&self.setup, &self.prove, &self.verify] {
+            for value in stats.csv_fields() {
+                write!(row, ",{:.3}", value).unwrap();
+            }
+        }
+        format!("{}\n{}\n", header, row)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("reports always serialize")
+    }
+
+    /// Writes `<binary>-<variant>.csv` and `.json` to `dir`, creating it if needed, and returns both paths
+    pub fn write(&self, dir: &Path) -> io::Result<(PathBuf, PathBuf)> {
+        fs::create_dir_all(dir)?;
+        let stem = format!("{}-{}", self.binary, self.variant);
+        let csv_path = dir.join(format!("{}.csv", stem));
+        let json_path = dir.join(format!("{}.json", stem));
+        fs::write(&csv_path, self.to_csv())?;
+        fs::write(&json_path, self.to_json())?;
+        Ok((csv_path, json_path))
+    }
+
+    /// Prints the counts and a table of the timing statistics
+    pub fn print(&self) {
+        println!("\n📊 === BENCHMARK RESULTS: {} ({} iterations, seed {}) ===", self.variant, self.iterations, self.seed);
+        println!("\n🏗️  Circuit Statistics:");
+        println!("   - Constraints: {}", self.constraints);
+        println!("   - Witnesses: {}", self.witnesses);
+        println!("   - Proof Size: {} bytes", self.proof_bytes);
+
+        println!("\n⏱️  Timing Analysis (ms):");
+        println!("   {:<8} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}", "", "Mean", "Median", "Std dev", "p95", "Min", "Max");
+        for (name, stats) in [("Setup", &self.setup), ("Prove", &self.prove), ("Verify", &self.verify)] {
+            let [mean, median, std_dev, p95, min, max] = stats.csv_fields();
+            println!(
+                "   {:<8} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2}",
+                name, mean, median, std_dev, p95, min, max
+            );
+        }
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|value| Duration::from_millis(*value)).collect()
+    }
+
+    #[test]
+    fn test_timing_stats() {
+        let stats = TimingStats::from_samples(&ms(&[40, 10, 30, 20]));
+        assert_eq!(stats.mean_ms, 25.0);
+        assert_eq!(stats.median_ms, 25.0);
+        assert!((stats.std_dev_ms - 12.909944).abs() < 1e-6);
+        assert_eq!(stats.p95_ms, 40.0);
+        assert_eq!((stats.min_ms, stats.max_ms), (10.0, 40.0));
+        assert_eq!(stats.samples_ms, vec![40.0, 10.0, 30.0, 20.0]);
+
+        // Nearest rank: the 95th percentile of 1..=100 is 95, of 1..=10 the maximum
+        let hundred: Vec<u64> = (1..=100).collect();
+        assert_eq!(TimingStats::from_samples(&ms(&hundred)).p95_ms, 95.0);
+        assert_eq!(TimingStats::from_samples(&ms(&[3, 1, 2])).median_ms, 2.0);
+
+        let single = TimingStats::from_samples(&ms(&[7]));
+        assert_eq!((single.std_dev_ms, single.p95_ms), (0.0, 7.0));
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let mut args: Vec<String> =
+            ["fr", "--seed", "7", "poseidon", "--iterations", "3", "--out", "out"].iter().map(|s| s.to_string()).collect();
+        let options = BenchmarkOptions::from_args(&mut args);
+        assert_eq!(options, BenchmarkOptions { iterations: 3, seed: 7, out_dir: PathBuf::from("out") });
+        assert_eq!(args, vec!["fr", "poseidon"]);
+
+        let mut args = vec!["sha256".to_string()];
+        assert_eq!(BenchmarkOptions::from_args(&mut args), BenchmarkOptions::default());
+        assert_eq!(args, vec!["sha256"]);
+    }
+
+    #[test]
+    fn test_report_files() {
+        let iteration = |setup, prove, verify| Iteration {
+            constraints: 100,
+            witnesses: 90,
+            proof_bytes: 192,
+            setup: Duration::from_millis(setup),
+            prove: Duration::from_millis(prove),
+            verify: Duration::from_millis(verify),
+        };
+        let options = BenchmarkOptions { out_dir: std::env::temp_dir().join("bench-report-test"), ..Default::default() };
+        let report = BenchmarkReport::new("elgamal-proof", "fr-sha256", &options, &[iteration(10, 20, 1), iteration(30, 40, 3)]);
+
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let header: Vec<&str> = lines[0].split(',').collect();
+        let row: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(header.len(), row.len());
+        assert_eq!(&row[..7], ["elgamal-proof", "fr-sha256", "2", "12345", "100", "90", "192"]);
+        let column = |name: &str| row[header.iter().position(|h| *h == name).unwrap()];
+        assert_eq!(column("prove_mean_ms"), "30.000");
+        assert_eq!(column("verify_max_ms"), "3.000");
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["variant"], "fr-sha256");
+        assert_eq!(json["setup"]["samples_ms"], serde_json::json!([10.0, 30.0]));
+
+        let (csv_path, json_path) = report.write(&options.out_dir).unwrap();
+        assert_eq!(fs::read_to_string(&csv_path).unwrap(), csv);
+        assert!(json_path.ends_with("elgamal-proof-fr-sha256.json"));
+        fs::remove_dir_all(&options.out_dir).unwrap();
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("fr"), "fr");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
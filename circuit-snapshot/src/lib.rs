@@ -0,0 +1,153 @@
+use ark_ff::PrimeField;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError};
+use std::fs;
+use std::path::Path;
+
+/// Set this environment variable to rewrite snapshot files with the current counts
+pub const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+
+/// Size of one circuit as recorded in a snapshot file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitCounts {
+    pub name: String,
+    pub constraints: usize,
+    pub witnesses: usize,
+}
+
+impl CircuitCounts {
+    /// Synthesizes `circuit` and records its constraint and witness variable counts
+    pub fn of<F: PrimeField, C: ConstraintSynthesizer<F>>(name: &str, circuit: C) -> Self {
+        let cs = ConstraintSystem::<F>::new_ref();
+        circuit.generate_constraints(cs.clone()).expect("circuit synthesis failed");
+        CircuitCounts {
+            name: name.to_string(),
+            constraints: cs.num_constraints(),
+            witnesses: cs.num_witness_variables(),
+        }
+    }
+}
+
+/// A single gadget wrapped as a circuit, so it can be counted and proven in isolation
+///
+/// The closure allocates the gadget's inputs and applies it, so counts include the inputs.
+#[derive(Clone)]
+pub struct GadgetCircuit<G>(pub G);
+
+impl<F, G> ConstraintSynthesizer<F> for GadgetCircuit<G>
+where
+    F: PrimeField,
+    G: FnOnce(ConstraintSystemRef<F>) -> Result<(), SynthesisError>,
+{
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        (self.0)(cs)
+    }
+}
+
+/// Parses a snapshot file: one `name constraints witnesses` line per circuit, `#` comments
+pub fn read_snapshot(path: &Path) -> Vec<CircuitCounts> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            assert_eq!(fields.len(), 3, "malformed snapshot line `{}`", line);
+            CircuitCounts {
+                name: fields[0].to_string(),
+                constraints: fields[1].parse().expect("constraint count"),
+                witnesses: fields[2].parse().expect("witness count"),
+            }
+        })
+        .collect()
+}
+
+/// Writes `counts` as a snapshot file
+pub fn write_snapshot(path: &Path, counts: &[CircuitCounts]) {
+    let mut contents = String::from(
+        "# Constraint and witness counts per circuit, checked by `cargo test`.\n\
+         # Regenerate with UPDATE_SNAPSHOTS=1 cargo test --release snapshot\n\
+         # circuit constraints witnesses\n",
+    );
+    for entry in counts {
+        contents.push_str(&format!("{} {} {}\n", entry.name, entry.constraints, entry.witnesses));
+    }
+    fs::write(path, contents).expect("failed to write snapshot");
+}
+
+/// Compares `counts` with the snapshot at `path`
+///
+/// Panics when a circuit is missing from the snapshot or any count grew. Decreases pass
+/// but are reported so the snapshot can be tightened. With `UPDATE_SNAPSHOTS` set the
+/// file is rewritten instead.
+pub fn check_snapshot(path: impl AsRef<Path>, counts: &[CircuitCounts]) {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        write_snapshot(path, counts);
+        println!("Updated {}", path.display());
+        return;
+    }
+
+    let recorded = read_snapshot(path);
+    let mut failures = Vec::new();
+    for entry in counts {
+        let Some(old) = recorded.iter().find(|old| old.name == entry.name) else {
+            failures.push(format!("{}: not in the snapshot", entry.name));
+            continue;
+        };
+        for (what, new, old) in [
+            ("constraints", entry.constraints, old.constraints),
+            ("witnesses", entry.witnesses, old.witnesses),
+        ] {
+            if new > old {
+                failures.push(format!("{}: {} grew from {} to {} (+{})", entry.name, what, old, new, new - old));
+            } else if new < old {
+                println!("{}: {} dropped from {} to {}; consider updating the snapshot", entry.name, what, old, new);
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "constraint snapshot {} is out of date:\n  {}\nRerun with {}=1 to accept the new counts",
+        path.display(),
+        failures.join("\n  "),
+        UPDATE_ENV
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(constraints: usize, witnesses: usize) -> Vec<CircuitCounts> {
+        vec![CircuitCounts { name: "circuit".to_string(), constraints, witnesses }]
+    }
+
+    fn snapshot_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("circuit-snapshot-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let path = snapshot_path("round-trip");
+        write_snapshot(&path, &counts(10, 5));
+        assert_eq!(read_snapshot(&path), counts(10, 5));
+        check_snapshot(&path, &counts(10, 5));
+        check_snapshot(&path, &counts(9, 5));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_rejects_growth_and_missing_circuits() {
+        let path = snapshot_path("growth");
+        write_snapshot(&path, &counts(10, 5));
+        for new in [counts(11, 5), counts(10, 6)] {
+            let result = std::panic::catch_unwind(|| check_snapshot(&path, &new));
+            assert!(result.is_err(), "growth must fail the snapshot check");
+        }
+        fs::remove_file(&path).unwrap();
+        let missing = std::panic::catch_unwind(|| check_snapshot(&path, &counts(10, 5)));
+        assert!(missing.is_err(), "circuits missing from the snapshot must fail");
+    }
+}
@@ -0,0 +1,84 @@
+//! Constraint counts, synthesis and proving time of each gadget in the ElGamal circuit
+//!
+//! Every gadget is synthesized on its own, with its inputs allocated as witnesses, and proven
+//! with Groth16 over BLS12-381. Run with `cargo bench --bench gadgets [-- <filter>]`; the
+//! counts are printed before each group's timings.
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::snark::SNARK;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::Groth16;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, uint8::UInt8};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use circuit_snapshot::{CircuitCounts, GadgetCircuit};
+use criterion::{criterion_group, criterion_main, Criterion};
+use elgamal_proof::circuit::OptimizedElGamalEncryptionCircuit;
+use elgamal_proof::elgamal::{elgamal_generator, setup_elgamal};
+use hash_gadgets::BlockIdHash;
+
+type Circuit = OptimizedElGamalEncryptionCircuit<Fr>;
+
+/// Prints the size of `circuit`, then benchmarks its synthesis and its Groth16 proof
+fn bench_circuit<C: ConstraintSynthesizer<Fr> + Clone>(c: &mut Criterion, name: &str, circuit: C) {
+    let counts = CircuitCounts::of::<Fr, _>(name, circuit.clone());
+    println!("{}: {} constraints, {} witnesses", name, counts.constraints, counts.witnesses);
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    let mut rng = StdRng::seed_from_u64(24);
+    // Criterion only runs the routine when the benchmark matches the filter, so setup stays lazy
+    let mut pk = None;
+    group.bench_function("synthesize", |b| b.iter(|| CircuitCounts::of::<Fr, _>(name, circuit.clone())));
+    group.bench_function("prove", |b| {
+        let pk = pk.get_or_insert_with(|| Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap().0);
+        b.iter(|| Groth16::<Bls12_381>::prove(pk, circuit.clone(), &mut rng).unwrap())
+    });
+    group.finish();
+}
+
+fn elgamal_gadgets(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(24);
+    let (c1, c2, hdk, message, hek, bid) = setup_elgamal::<Fr, _>(&mut rng);
+
+    bench_circuit(c, "exponent_bits", GadgetCircuit(move |cs: ConstraintSystemRef<Fr>| {
+        Circuit::alloc_exponent_bits(cs, hdk)?;
+        Ok(())
+    }));
+
+    bench_circuit(c, "variable_base_exponentiation", GadgetCircuit(move |cs: ConstraintSystemRef<Fr>| {
+        let base = FpVar::new_witness(cs.clone(), || Ok(c1))?;
+        let bits = Circuit::alloc_exponent_bits(cs, hdk)?;
+        let _ = Circuit::variable_base_exponentiation(&base, &bits)?;
+        Ok(())
+    }));
+
+    bench_circuit(c, "fixed_base_exponentiation", GadgetCircuit(move |cs: ConstraintSystemRef<Fr>| {
+        let bits = Circuit::alloc_exponent_bits(cs, hdk)?;
+        let _ = Circuit::fixed_base_exponentiation(elgamal_generator(), &bits)?;
+        Ok(())
+    }));
+
+    bench_circuit(c, "field_to_bytes_optimized", GadgetCircuit(move |cs: ConstraintSystemRef<Fr>| {
+        let m = FpVar::new_witness(cs, || Ok(message))?;
+        Circuit::field_to_bytes_optimized(&m)?;
+        Ok(())
+    }));
+
+    // The block ID hashes of the message bytes; `block_id_sha256` is the bare `Sha256Gadget`
+    let message_bytes: [u8; 32] = message.into_bigint().to_bytes_le().try_into().unwrap();
+    for hash in [BlockIdHash::Sha256, BlockIdHash::Keccak256, BlockIdHash::Poseidon] {
+        let name = format!("block_id_{:?}", hash).to_lowercase();
+        bench_circuit(c, &name, GadgetCircuit(move |cs: ConstraintSystemRef<Fr>| {
+            let bytes = UInt8::new_witness_vec(cs, &message_bytes)?;
+            hash.block_id_gadget(&bytes)?;
+            Ok(())
+        }));
+    }
+
+    let circuit = Circuit { ct: [c1, c2], hek, bid, hdk, hash: BlockIdHash::Sha256 };
+    bench_circuit(c, "elgamal_circuit", circuit);
+}
+
+criterion_group!(benches, elgamal_gadgets);
+criterion_main!(benches);
@@ -0,0 +1,603 @@
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::scalar_mul::ScalarMul;
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup, VariableBaseMSM};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_groth16::{PreparedVerifyingKey, Proof};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Domain separator for the Fiat-Shamir transcript of an aggregate proof
+const TRANSCRIPT_DOMAIN: &[u8] = b"elgamal-proof/snarkpack/v1";
+
+/// A pairing commitment, one target group element under each SRS trapdoor
+pub type Commitment<E> = (PairingOutput<E>, PairingOutput<E>);
+
+/// Structured reference string for aggregating up to `max_proofs` Groth16 proofs
+///
+/// Holds powers of two independent trapdoors a and b: g^(a^i) and g^(b^i) for i < 2n in G1 and
+/// h^(a^i) and h^(b^i) for i < n in G2. A G1 vector is committed under the G2 key v = (h^(a^i), h^(b^i))
+/// and a G2 vector under the G1 key w = (g^(a^(n+i)), g^(b^(n+i))). The two keys use disjoint powers
+/// so that a commitment to a pair of vectors binds both halves.
+#[derive(Clone, Debug)]
+pub struct AggregationSrs<E: Pairing> {
+    pub g_alpha_powers: Vec<E::G1Affine>,
+    pub g_beta_powers: Vec<E::G1Affine>,
+    pub h_alpha_powers: Vec<E::G2Affine>,
+    pub h_beta_powers: Vec<E::G2Affine>,
+}
+
+/// The part of the SRS needed to verify an aggregate proof
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregationVerifierKey<E: Pairing> {
+    pub g: E::G1Affine,
+    pub h: E::G2Affine,
+    pub g_alpha: E::G1Affine,
+    pub g_beta: E::G1Affine,
+    pub h_alpha: E::G2Affine,
+    pub h_beta: E::G2Affine,
+}
+
+impl<E: Pairing> AggregationSrs<E> {
+    /// Largest number of proofs this SRS can aggregate
+    pub fn max_proofs(&self) -> usize {
+        self.h_alpha_powers.len()
+    }
+
+    pub fn verifier_key(&self) -> AggregationVerifierKey<E> {
+        AggregationVerifierKey {
+            g: self.g_alpha_powers[0],
+            h: self.h_alpha_powers[0],
+            g_alpha: self.g_alpha_powers[1],
+            g_beta: self.g_beta_powers[1],
+            h_alpha: self.h_alpha_powers[1],
+            h_beta: self.h_beta_powers[1],
+        }
+    }
+}
+
+/// Samples an SRS for up to `max_proofs` proofs (rounded up to a power of two) from local trapdoors
+///
+/// Whoever runs this knows a and b and can forge aggregates, so it is only suitable for benchmarks
+/// and tests. A deployment would take the powers from a multi-party ceremony instead.
+pub fn setup_simulated_srs<E: Pairing, R: Rng>(rng: &mut R, max_proofs: usize) -> AggregationSrs<E> {
+    let n = max_proofs.max(2).next_power_of_two();
+    let alpha = E::ScalarField::rand(rng);
+    let beta = E::ScalarField::rand(rng);
+    let g = E::G1::generator();
+    let h = E::G2::generator();
+    AggregationSrs {
+        g_alpha_powers: g.batch_mul(&powers(alpha, 2 * n)),
+        g_beta_powers: g.batch_mul(&powers(beta, 2 * n)),
+        h_alpha_powers: h.batch_mul(&powers(alpha, n)),
+        h_beta_powers: h.batch_mul(&powers(beta, n)),
+    }
+}
+
+/// Cross terms the prover sends in one halving round
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GipaRound<E: Pairing> {
+    /// Commitment to (A_R, B_L) under (v_L, w_R), scaled by x when folding
+    pub ab_left: Commitment<E>,
+    /// Commitment to (A_L, B_R) under (v_R, w_L), scaled by 1/x when folding
+    pub ab_right: Commitment<E>,
+    /// <A_R, B_L>
+    pub z_ab_left: PairingOutput<E>,
+    /// <A_L, B_R>
+    pub z_ab_right: PairingOutput<E>,
+    /// Commitment to C_R under v_L
+    pub c_left: Commitment<E>,
+    /// Commitment to C_L under v_R
+    pub c_right: Commitment<E>,
+    /// <C_R, s_L>
+    pub z_c_left: E::G1Affine,
+    /// <C_L, s_R>
+    pub z_c_right: E::G1Affine,
+}
+
+/// Aggregate of N Groth16 proofs under one verifying key
+///
+/// Its size grows with log2(N): a fixed header, one `GipaRound` per halving and the folded
+/// elements with their key openings.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregateProof<E: Pairing> {
+    /// Commitment to the A and B elements of every proof
+    pub com_ab: Commitment<E>,
+    /// Commitment to the C elements of every proof
+    pub com_c: Commitment<E>,
+    /// prod e(A_i, B_i)^(r^i)
+    pub z_ab: PairingOutput<E>,
+    /// sum r^i C_i
+    pub z_c: E::G1Affine,
+    pub rounds: Vec<GipaRound<E>>,
+    pub final_a: E::G1Affine,
+    pub final_b: E::G2Affine,
+    pub final_c: E::G1Affine,
+    /// Folded commitment keys
+    pub final_v: (E::G2Affine, E::G2Affine),
+    pub final_w: (E::G1Affine, E::G1Affine),
+    /// KZG openings showing the folded keys were derived from the SRS
+    pub v_opening: (E::G2Affine, E::G2Affine),
+    pub w_opening: (E::G1Affine, E::G1Affine),
+}
+
+/// Why proofs could not be aggregated, or why an aggregate was rejected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AggregationError {
+    /// There are no proofs to aggregate
+    NoProofs,
+    /// The SRS supports fewer proofs than were given
+    SrsTooSmall { proofs: usize, max_proofs: usize },
+    /// Proof `index` has a different number of public inputs than the verifying key expects
+    WrongInputCount { index: usize },
+    /// The aggregate has the wrong number of halving rounds for the number of proofs
+    WrongRoundCount,
+    /// The folded commitments or products do not match the final elements
+    InvalidFolding,
+    /// The folded commitment keys were not derived from the SRS
+    InvalidKeyOpening,
+    /// The aggregated Groth16 equation does not hold
+    InvalidGroth16Equation,
+}
+
+impl fmt::Display for AggregationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregationError::NoProofs => write!(f, "no proofs to aggregate"),
+            AggregationError::SrsTooSmall { proofs, max_proofs } => {
+                write!(f, "{} proofs exceed the SRS limit of {}", proofs, max_proofs)
+            }
+            AggregationError::WrongInputCount { index } => write!(f, "proof {} has the wrong number of public inputs", index),
+            AggregationError::WrongRoundCount => write!(f, "aggregate has the wrong number of rounds"),
+            AggregationError::InvalidFolding => write!(f, "aggregate does not fold to its final elements"),
+            AggregationError::InvalidKeyOpening => write!(f, "aggregate commitment keys do not match the SRS"),
+            AggregationError::InvalidGroth16Equation => write!(f, "aggregated Groth16 equation does not hold"),
+        }
+    }
+}
+
+impl std::error::Error for AggregationError {}
+
+/// Fiat-Shamir transcript: a SHA-256 chain over everything the prover has sent so far
+struct Transcript {
+    state: [u8; 32],
+}
+
+impl Transcript {
+    fn new() -> Self {
+        Transcript { state: Sha256::digest(TRANSCRIPT_DOMAIN).into() }
+    }
+
+    fn append<T: CanonicalSerialize + ?Sized>(&mut self, value: &T) {
+        let mut bytes = Vec::new();
+        value.serialize_compressed(&mut bytes).unwrap();
+        self.state = Sha256::new().chain_update(self.state).chain_update(bytes).finalize().into();
+    }
+
+    /// Nonzero, hence invertible, challenge bound to the transcript so far
+    fn challenge<F: PrimeField>(&mut self) -> F {
+        loop {
+            self.state = Sha256::new().chain_update(self.state).chain_update(b"challenge").finalize().into();
+            let challenge = F::from_le_bytes_mod_order(&self.state);
+            if !challenge.is_zero() {
+                return challenge;
+            }
+        }
+    }
+}
+
+/// 1, x, x^2, ..., x^(count - 1)
+fn powers<F: Field>(x: F, count: usize) -> Vec<F> {
+    std::iter::successors(Some(F::one()), |power| Some(*power * x)).take(count).collect()
+}
+
+/// Commitment to the G1 vector `a` under (v1, v2) and the G2 vector `b` under (w1, w2)
+fn commit_pair<E: Pairing>(
+    a: &[E::G1Affine],
+    b: &[E::G2Affine],
+    v: (&[E::G2Affine], &[E::G2Affine]),
+    w: (&[E::G1Affine], &[E::G1Affine]),
+) -> Commitment<E> {
+    (
+        E::multi_pairing(a.iter().chain(w.0).copied(), v.0.iter().chain(b).copied()),
+        E::multi_pairing(a.iter().chain(w.1).copied(), v.1.iter().chain(b).copied()),
+    )
+}
+
+/// Commitment to the G1 vector `c` under (v1, v2)
+fn commit_single<E: Pairing>(c: &[E::G1Affine], v: (&[E::G2Affine], &[E::G2Affine])) -> Commitment<E> {
+    (E::multi_pairing(c.iter().copied(), v.0.iter().copied()), E::multi_pairing(c.iter().copied(), v.1.iter().copied()))
+}
+
+/// left + x * right, elementwise
+fn fold<G: CurveGroup>(left: &[G::Affine], right: &[G::Affine], x: G::ScalarField) -> Vec<G::Affine> {
+    let folded: Vec<G> = left.iter().zip(right).map(|(l, r)| *r * x + l).collect();
+    G::normalize_batch(&folded)
+}
+
+/// Coefficients of prod_j (1 + factors[j] X^(2^(k-1-j))) for k = factors.len()
+///
+/// This is how a vector of length 2^k folds down to one element: round j halves the vector and
+/// multiplies the upper half's monomials by the round's factor.
+fn product_polynomial<F: Field>(factors: &[F]) -> Vec<F> {
+    let mut coeffs = vec![F::one()];
+    for factor in factors.iter().rev() {
+        let upper: Vec<F> = coeffs.iter().map(|coeff| *coeff * factor).collect();
+        coeffs.extend(upper);
+    }
+    coeffs
+}
+
+/// Evaluates `product_polynomial(factors)` at z in O(log n)
+fn evaluate_product<F: Field>(factors: &[F], z: F) -> F {
+    let mut z_power = z;
+    let mut result = F::one();
+    for factor in factors.iter().rev() {
+        result *= F::one() + *factor * z_power;
+        z_power.square_in_place();
+    }
+    result
+}
+
+/// KZG opening of the polynomial `coeffs` at z: a commitment to (p(X) - p(z)) / (X - z)
+fn kzg_open<G: CurveGroup>(powers: &[G::Affine], coeffs: &[G::ScalarField], z: G::ScalarField) -> G::Affine {
+    let mut quotient = vec![G::ScalarField::zero(); coeffs.len() - 1];
+    let mut carry = G::ScalarField::zero();
+    for i in (1..coeffs.len()).rev() {
+        carry = coeffs[i] + carry * z;
+        quotient[i - 1] = carry;
+    }
+    G::msm(&powers[..quotient.len()], &quotient).unwrap().into_affine()
+}
+
+/// Factors of the folded v key: round j contributes 1 / (x_j r^(2^(k-1-j)))
+fn v_key_factors<F: Field>(challenges: &[F], r_inv: F) -> Vec<F> {
+    let k = challenges.len();
+    challenges
+        .iter()
+        .enumerate()
+        .map(|(j, x)| x.inverse().unwrap() * r_inv.pow([1u64 << (k - 1 - j)]))
+        .collect()
+}
+
+/// Aggregates Groth16 proofs that share a verifying key into one `AggregateProof`
+///
+/// Follows SnarkPack (Gailly, Maller, Nitulescu 2021). The prover commits to the A, B and C elements
+/// of every proof, derives r from the commitments and the public inputs, and proves with one
+/// GIPA recursion that the committed vectors satisfy
+///
+///   prod e(A_i, B_i)^(r^i) = Z_AB  and  sum r^i C_i = Z_C
+///
+/// The verifier then checks the random linear combination of the N Groth16 equations with three
+/// pairings. Each GIPA round halves the vectors, so an aggregate of N proofs has log2(N) rounds.
+/// The batch is padded to a power of two by repeating the last proof.
+pub fn aggregate_proofs<E: Pairing>(
+    srs: &AggregationSrs<E>,
+    proofs: &[Proof<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+) -> Result<AggregateProof<E>, AggregationError> {
+    assert_eq!(proofs.len(), public_inputs.len(), "every proof needs its public inputs");
+    if proofs.is_empty() {
+        return Err(AggregationError::NoProofs);
+    }
+    let n = proofs.len().next_power_of_two();
+    if n > srs.max_proofs() {
+        return Err(AggregationError::SrsTooSmall { proofs: proofs.len(), max_proofs: srs.max_proofs() });
+    }
+
+    let padded = |i: usize| &proofs[i.min(proofs.len() - 1)];
+    let a: Vec<E::G1Affine> = (0..n).map(|i| padded(i).a).collect();
+    let b: Vec<E::G2Affine> = (0..n).map(|i| padded(i).b).collect();
+    let c: Vec<E::G1Affine> = (0..n).map(|i| padded(i).c).collect();
+    let v = (&srs.h_alpha_powers[..n], &srs.h_beta_powers[..n]);
+    let w = (&srs.g_alpha_powers[n..2 * n], &srs.g_beta_powers[n..2 * n]);
+
+    let com_ab = commit_pair::<E>(&a, &b, v, w);
+    let com_c = commit_single::<E>(&c, v);
+
+    let mut transcript = Transcript::new();
+    transcript.append(public_inputs);
+    transcript.append(&com_ab);
+    transcript.append(&com_c);
+    let r: E::ScalarField = transcript.challenge();
+    let r_inv = r.inverse().unwrap();
+
+    // Scaling A and C by r^i and the v key by r^-i leaves both commitments unchanged
+    let scale_g1 = |points: &[E::G1Affine], scalars: &[E::ScalarField]| {
+        E::G1::normalize_batch(&points.iter().zip(scalars).map(|(p, s)| *p * s).collect::<Vec<_>>())
+    };
+    let scale_g2 = |points: &[E::G2Affine], scalars: &[E::ScalarField]| {
+        E::G2::normalize_batch(&points.iter().zip(scalars).map(|(p, s)| *p * s).collect::<Vec<_>>())
+    };
+    let r_powers = powers(r, n);
+    let r_inv_powers = powers(r_inv, n);
+    let mut a = scale_g1(&a, &r_powers);
+    let mut c = scale_g1(&c, &r_powers);
+    let mut b = b;
+    let mut v1 = scale_g2(v.0, &r_inv_powers);
+    let mut v2 = scale_g2(v.1, &r_inv_powers);
+    let mut w1 = w.0.to_vec();
+    let mut w2 = w.1.to_vec();
+    let mut s = vec![E::ScalarField::one(); n];
+
+    let z_ab = E::multi_pairing(a.iter().copied(), b.iter().copied());
+    let z_c = c.iter().map(|point| point.into_group()).sum::<E::G1>().into_affine();
+    transcript.append(&z_ab);
+    transcript.append(&z_c);
+
+    let mut rounds = Vec::new();
+    let mut challenges = Vec::new();
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_l, a_r) = a.split_at(half);
+        let (b_l, b_r) = b.split_at(half);
+        let (c_l, c_r) = c.split_at(half);
+        let (v1_l, v1_r) = v1.split_at(half);
+        let (v2_l, v2_r) = v2.split_at(half);
+        let (w1_l, w1_r) = w1.split_at(half);
+        let (w2_l, w2_r) = w2.split_at(half);
+        let (s_l, s_r) = s.split_at(half);
+
+        let round = GipaRound {
+            ab_left: commit_pair::<E>(a_r, b_l, (v1_l, v2_l), (w1_r, w2_r)),
+            ab_right: commit_pair::<E>(a_l, b_r, (v1_r, v2_r), (w1_l, w2_l)),
+            z_ab_left: E::multi_pairing(a_r.iter().copied(), b_l.iter().copied()),
+            z_ab_right: E::multi_pairing(a_l.iter().copied(), b_r.iter().copied()),
+            c_left: commit_single::<E>(c_r, (v1_l, v2_l)),
+            c_right: commit_single::<E>(c_l, (v1_r, v2_r)),
+            z_c_left: E::G1::msm(c_r, s_l).unwrap().into_affine(),
+            z_c_right: E::G1::msm(c_l, s_r).unwrap().into_affine(),
+        };
+        transcript.append(&round);
+        let x: E::ScalarField = transcript.challenge();
+        let x_inv = x.inverse().unwrap();
+
+        let next_a = fold::<E::G1>(a_l, a_r, x);
+        let next_b = fold::<E::G2>(b_l, b_r, x_inv);
+        let next_c = fold::<E::G1>(c_l, c_r, x);
+        let next_v1 = fold::<E::G2>(v1_l, v1_r, x_inv);
+        let next_v2 = fold::<E::G2>(v2_l, v2_r, x_inv);
+        let next_w1 = fold::<E::G1>(w1_l, w1_r, x);
+        let next_w2 = fold::<E::G1>(w2_l, w2_r, x);
+        s = s_l.iter().zip(s_r).map(|(l, r)| *l + x_inv * r).collect();
+        (a, b, c, v1, v2, w1, w2) = (next_a, next_b, next_c, next_v1, next_v2, next_w1, next_w2);
+
+        rounds.push(round);
+        challenges.push(x);
+    }
+
+    let final_v = (v1[0], v2[0]);
+    let final_w = (w1[0], w2[0]);
+    transcript.append(&(a[0], b[0], c[0]));
+    transcript.append(&(final_v, final_w));
+    let z: E::ScalarField = transcript.challenge();
+
+    // v folds to h^f_v(a) for the rescaled key, w to g^(a^n f_w(a))
+    let v_coeffs = product_polynomial(&v_key_factors(&challenges, r_inv));
+    let mut w_coeffs = vec![E::ScalarField::zero(); n];
+    w_coeffs.extend(product_polynomial(&challenges));
+
+    Ok(AggregateProof {
+        com_ab,
+        com_c,
+        z_ab,
+        z_c,
+        rounds,
+        final_a: a[0],
+        final_b: b[0],
+        final_c: c[0],
+        final_v,
+        final_w,
+        v_opening: (
+            kzg_open::<E::G2>(&srs.h_alpha_powers, &v_coeffs, z),
+            kzg_open::<E::G2>(&srs.h_beta_powers, &v_coeffs, z),
+        ),
+        w_opening: (
+            kzg_open::<E::G1>(&srs.g_alpha_powers, &w_coeffs, z),
+            kzg_open::<E::G1>(&srs.g_beta_powers, &w_coeffs, z),
+        ),
+    })
+}
+
+/// Checks an aggregate of proofs for `public_inputs` under one Groth16 verifying key
+///
+/// Replays the GIPA folding, checks the folded commitments open to the final elements, checks
+/// the KZG openings of the folded keys and finally the aggregated Groth16 equation
+///
+///   Z_AB = e(alpha, beta)^(sum r^i) * e(sum r^i IC_i, gamma) * e(Z_C, delta)
+///
+/// Verification costs O(log N) pairings plus one MSM over the public inputs.
+pub fn verify_aggregate_proof<E: Pairing>(
+    srs_vk: &AggregationVerifierKey<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &AggregateProof<E>,
+) -> Result<(), AggregationError> {
+    let vk = &pvk.vk;
+    if public_inputs.is_empty() {
+        return Err(AggregationError::NoProofs);
+    }
+    if let Some(index) = public_inputs.iter().position(|inputs| inputs.len() + 1 != vk.gamma_abc_g1.len()) {
+        return Err(AggregationError::WrongInputCount { index });
+    }
+    let n = public_inputs.len().next_power_of_two();
+    if proof.rounds.len() != n.trailing_zeros() as usize {
+        return Err(AggregationError::WrongRoundCount);
+    }
+
+    let mut transcript = Transcript::new();
+    transcript.append(public_inputs);
+    transcript.append(&proof.com_ab);
+    transcript.append(&proof.com_c);
+    let r: E::ScalarField = transcript.challenge();
+    let r_inv = r.inverse().unwrap();
+    transcript.append(&proof.z_ab);
+    transcript.append(&proof.z_c);
+
+    let (mut com_ab, mut com_c, mut z_ab, mut z_c) = (proof.com_ab, proof.com_c, proof.z_ab, proof.z_c.into_group());
+    let mut challenges = Vec::new();
+    for round in &proof.rounds {
+        transcript.append(round);
+        let x: E::ScalarField = transcript.challenge();
+        let x_inv = x.inverse().unwrap();
+        com_ab.0 += round.ab_left.0 * x + round.ab_right.0 * x_inv;
+        com_ab.1 += round.ab_left.1 * x + round.ab_right.1 * x_inv;
+        z_ab += round.z_ab_left * x + round.z_ab_right * x_inv;
+        com_c.0 += round.c_left.0 * x + round.c_right.0 * x_inv;
+        com_c.1 += round.c_left.1 * x + round.c_right.1 * x_inv;
+        z_c += round.z_c_left * x + round.z_c_right * x_inv;
+        challenges.push(x);
+    }
+
+    // s starts as all ones and folds with 1/x like the v key
+    let inverses: Vec<E::ScalarField> = challenges.iter().map(|x| x.inverse().unwrap()).collect();
+    let s = evaluate_product(&inverses, E::ScalarField::one());
+    let v = ([proof.final_v.0], [proof.final_v.1]);
+    let w = ([proof.final_w.0], [proof.final_w.1]);
+    if com_ab != commit_pair::<E>(&[proof.final_a], &[proof.final_b], (&v.0, &v.1), (&w.0, &w.1))
+        || z_ab != E::pairing(proof.final_a, proof.final_b)
+        || com_c != commit_single::<E>(&[proof.final_c], (&v.0, &v.1))
+        || z_c != proof.final_c * s
+    {
+        return Err(AggregationError::InvalidFolding);
+    }
+
+    transcript.append(&(proof.final_a, proof.final_b, proof.final_c));
+    transcript.append(&(proof.final_v, proof.final_w));
+    let z: E::ScalarField = transcript.challenge();
+
+    // e(g, v - h^f(z)) = e(g^a / g^z, opening) for each trapdoor, and the same with the groups swapped for w
+    let fv_z = evaluate_product(&v_key_factors(&challenges, r_inv), z);
+    let fw_z = z.pow([n as u64]) * evaluate_product(&challenges, z);
+    let (g, h) = (srs_vk.g.into_group(), srs_vk.h.into_group());
+    let v_holds = |key: E::G2Affine, g_trapdoor: E::G1Affine, opening: E::G2Affine| {
+        E::multi_pairing([g, g * z - g_trapdoor], [key.into_group() - h * fv_z, opening.into_group()]).is_zero()
+    };
+    let w_holds = |key: E::G1Affine, h_trapdoor: E::G2Affine, opening: E::G1Affine| {
+        E::multi_pairing([key.into_group() - g * fw_z, opening.into_group()], [h, h * z - h_trapdoor]).is_zero()
+    };
+    if !v_holds(proof.final_v.0, srs_vk.g_alpha, proof.v_opening.0)
+        || !v_holds(proof.final_v.1, srs_vk.g_beta, proof.v_opening.1)
+        || !w_holds(proof.final_w.0, srs_vk.h_alpha, proof.w_opening.0)
+        || !w_holds(proof.final_w.1, srs_vk.h_beta, proof.w_opening.1)
+    {
+        return Err(AggregationError::InvalidKeyOpening);
+    }
+
+    // sum r^i IC_i over the padded batch, as in `batch_verify`
+    let r_powers = powers(r, n);
+    let padded = |i: usize| &public_inputs[i.min(public_inputs.len() - 1)];
+    let r_sum: E::ScalarField = r_powers.iter().sum();
+    let mut input_scalars = vec![r_sum];
+    input_scalars.extend(
+        (0..vk.gamma_abc_g1.len() - 1)
+            .map(|j| r_powers.iter().enumerate().map(|(i, r_i)| *r_i * padded(i)[j]).sum::<E::ScalarField>()),
+    );
+    let combined_inputs = E::G1::msm(&vk.gamma_abc_g1, &input_scalars).unwrap();
+
+    let expected = PairingOutput(pvk.alpha_g1_beta_g2) * r_sum
+        + E::multi_pairing([combined_inputs, proof.z_c.into_group()], [vk.gamma_g2, vk.delta_g2]);
+    if proof.z_ab != expected {
+        return Err(AggregationError::InvalidGroth16Equation);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::OptimizedElGamalEncryptionCircuit;
+    use crate::elgamal::setup_elgamal;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_crypto_primitives::snark::SNARK;
+    use ark_groth16::{prepare_verifying_key, Groth16};
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use hash_gadgets::BlockIdHash;
+
+    /// Proofs of `count` random blocks under one key, with Poseidon block IDs to keep proving fast
+    fn prove_blocks(count: usize) -> (PreparedVerifyingKey<Bls12_381>, Vec<Proof<Bls12_381>>, Vec<Vec<Fr>>) {
+        let mut rng = StdRng::seed_from_u64(20);
+        let circuits: Vec<_> = (0..count)
+            .map(|_| {
+                let (c1, c2, hdk, message, hek, _bid) = setup_elgamal::<Fr, _>(&mut rng);
+                let hash = BlockIdHash::Poseidon;
+                OptimizedElGamalEncryptionCircuit { ct: [c1, c2], hek, bid: hash.block_id(&message), hdk, hash }
+            })
+            .collect();
+
+        let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(circuits[0].clone(), &mut rng).unwrap();
+        let mut proofs = Vec::new();
+        let mut inputs = Vec::new();
+        for circuit in circuits {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit.clone().generate_constraints(cs.clone()).unwrap();
+            inputs.push(cs.borrow().unwrap().instance_assignment[1..].to_vec());
+            proofs.push(Groth16::<Bls12_381>::prove(&pk, circuit, &mut rng).unwrap());
+        }
+        (prepare_verifying_key(&vk), proofs, inputs)
+    }
+
+    #[test]
+    fn test_aggregation() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let srs = setup_simulated_srs::<Bls12_381, _>(&mut rng, 8);
+        let srs_vk = srs.verifier_key();
+        let (pvk, proofs, inputs) = prove_blocks(5);
+
+        // 5 proofs are padded to 8, so three rounds
+        let aggregate = aggregate_proofs(&srs, &proofs, &inputs).unwrap();
+        assert_eq!(aggregate.rounds.len(), 3);
+        assert_eq!(verify_aggregate_proof(&srs_vk, &pvk, &inputs, &aggregate), Ok(()));
+
+        let mut bytes = Vec::new();
+        aggregate.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(AggregateProof::<Bls12_381>::deserialize_compressed(&bytes[..]).unwrap(), aggregate);
+
+        let single = aggregate_proofs(&srs, &proofs[..1], &inputs[..1]).unwrap();
+        assert!(single.rounds.is_empty());
+        assert_eq!(verify_aggregate_proof(&srs_vk, &pvk, &inputs[..1], &single), Ok(()));
+
+        // The aggregate only covers the inputs it was made for
+        assert_eq!(verify_aggregate_proof(&srs_vk, &pvk, &inputs[..4], &aggregate), Err(AggregationError::WrongRoundCount));
+        let mut tampered = inputs.clone();
+        tampered[3][1] += Fr::from(1u64);
+        assert!(verify_aggregate_proof(&srs_vk, &pvk, &tampered, &aggregate).is_err());
+
+        assert_eq!(aggregate_proofs(&srs, &[], &[]), Err(AggregationError::NoProofs));
+        let too_many: Vec<_> = proofs.iter().cycle().take(9).cloned().collect();
+        let too_many_inputs: Vec<_> = inputs.iter().cycle().take(9).cloned().collect();
+        assert_eq!(
+            aggregate_proofs(&srs, &too_many, &too_many_inputs),
+            Err(AggregationError::SrsTooSmall { proofs: 9, max_proofs: 8 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_aggregates_are_rejected() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let srs = setup_simulated_srs::<Bls12_381, _>(&mut rng, 4);
+        let srs_vk = srs.verifier_key();
+        let (pvk, proofs, inputs) = prove_blocks(4);
+
+        // An invalid proof aggregates, but the aggregate fails the Groth16 equation
+        let mut swapped = proofs.clone();
+        swapped.swap(0, 2);
+        let aggregate = aggregate_proofs(&srs, &swapped, &inputs).unwrap();
+        assert_eq!(
+            verify_aggregate_proof(&srs_vk, &pvk, &inputs, &aggregate),
+            Err(AggregationError::InvalidGroth16Equation)
+        );
+
+        let aggregate = aggregate_proofs(&srs, &proofs, &inputs).unwrap();
+        let mut forged = aggregate.clone();
+        forged.rounds[1].z_ab_left += aggregate.z_ab;
+        assert_eq!(verify_aggregate_proof(&srs_vk, &pvk, &inputs, &forged), Err(AggregationError::InvalidFolding));
+
+        // Keys folded from a different SRS cannot be opened against this one
+        let other = setup_simulated_srs::<Bls12_381, _>(&mut rng, 4);
+        let forged = aggregate_proofs(&other, &proofs, &inputs).unwrap();
+        assert_eq!(verify_aggregate_proof(&srs_vk, &pvk, &inputs, &forged), Err(AggregationError::InvalidKeyOpening));
+    }
+}
@@ -0,0 +1,174 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, PrimeField};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
+use ark_std::rand::Rng;
+use std::fmt;
+
+/// Why a batch of proofs was rejected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchVerifyError {
+    /// Proof `index` has a different number of public inputs than the verifying key expects
+    WrongInputCount { index: usize },
+    /// The combined check failed; these proofs fail on their own
+    InvalidProofs(Vec<usize>),
+}
+
+impl fmt::Display for BatchVerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchVerifyError::WrongInputCount { index } => write!(f, "proof {} has the wrong number of public inputs", index),
+            BatchVerifyError::InvalidProofs(indices) => write!(f, "invalid proofs at {:?}", indices),
+        }
+    }
+}
+
+impl std::error::Error for BatchVerifyError {}
+
+/// Checks many Groth16 proofs under one verifying key with a single multi-pairing
+///
+/// Each proof i satisfies e(A_i, B_i) = e(alpha, beta) * e(IC_i, gamma) * e(C_i, delta).
+/// Raising equation i to a random 128-bit r_i and multiplying them all gives
+///
+///   prod e(r_i A_i, B_i) * e(sum r_i IC_i, -gamma) * e(sum r_i C_i, -delta) = e(alpha, beta)^(sum r_i)
+///
+/// which costs N + 2 Miller loops and one final exponentiation instead of 3N pairings.
+/// sum r_i IC_i is a single MSM over the verifying key's input bases. A batch containing an
+/// invalid proof passes with probability at most 2^-128. When the batch check fails every
+/// proof is verified on its own to report which ones are invalid.
+pub fn batch_verify<E: Pairing, R: Rng>(
+    pvk: &PreparedVerifyingKey<E>,
+    proofs: &[Proof<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+    rng: &mut R,
+) -> Result<(), BatchVerifyError> {
+    assert_eq!(proofs.len(), public_inputs.len(), "every proof needs its public inputs");
+    let vk = &pvk.vk;
+    if let Some(index) = public_inputs.iter().position(|inputs| inputs.len() + 1 != vk.gamma_abc_g1.len()) {
+        return Err(BatchVerifyError::WrongInputCount { index });
+    }
+    if proofs.is_empty() {
+        return Ok(());
+    }
+
+    let r: Vec<E::ScalarField> = proofs.iter().map(|_| E::ScalarField::from(rng.gen::<u128>())).collect();
+    let r_sum: E::ScalarField = r.iter().sum();
+
+    // sum r_i IC_i = (sum r_i) * gamma_abc[0] + sum_j (sum_i r_i x_ij) * gamma_abc[j + 1]
+    let mut input_scalars = vec![r_sum];
+    input_scalars.extend((0..vk.gamma_abc_g1.len() - 1).map(|j| {
+        r.iter().zip(public_inputs.iter()).map(|(r_i, inputs)| *r_i * inputs[j]).sum::<E::ScalarField>()
+    }));
+    let combined_inputs = E::G1::msm(&vk.gamma_abc_g1, &input_scalars).unwrap();
+
+    let c_bases: Vec<E::G1Affine> = proofs.iter().map(|proof| proof.c).collect();
+    let combined_c = E::G1::msm(&c_bases, &r).unwrap();
+
+    let mut g1: Vec<E::G1Prepared> = proofs
+        .iter()
+        .zip(r.iter())
+        .map(|(proof, r_i)| (proof.a * r_i).into_affine().into())
+        .collect();
+    let mut g2: Vec<E::G2Prepared> = proofs.iter().map(|proof| proof.b.into()).collect();
+    g1.push(combined_inputs.into_affine().into());
+    g2.push(pvk.gamma_g2_neg_pc.clone());
+    g1.push(combined_c.into_affine().into());
+    g2.push(pvk.delta_g2_neg_pc.clone());
+
+    let combined = E::final_exponentiation(E::multi_miller_loop(g1, g2));
+    if combined.map(|output| output.0) == Some(pvk.alpha_g1_beta_g2.pow(r_sum.into_bigint())) {
+        return Ok(());
+    }
+
+    let invalid = proofs
+        .iter()
+        .zip(public_inputs.iter())
+        .enumerate()
+        .filter(|(_, (proof, inputs))| !Groth16::<E>::verify_proof(pvk, proof, inputs).unwrap_or(false))
+        .map(|(index, _)| index)
+        .collect();
+    Err(BatchVerifyError::InvalidProofs(invalid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::OptimizedElGamalEncryptionCircuit;
+    use crate::elgamal::setup_elgamal;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_crypto_primitives::snark::SNARK;
+    use ark_ec::AffineRepr;
+    use ark_groth16::prepare_verifying_key;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use hash_gadgets::BlockIdHash;
+
+    /// Proofs of `count` random blocks under one key, with Poseidon block IDs to keep proving fast
+    fn prove_blocks(count: usize) -> (PreparedVerifyingKey<Bls12_381>, Vec<Proof<Bls12_381>>, Vec<Vec<Fr>>) {
+        let mut rng = StdRng::seed_from_u64(19);
+        let circuits: Vec<_> = (0..count)
+            .map(|_| {
+                let (c1, c2, hdk, message, hek, _bid) = setup_elgamal::<Fr, _>(&mut rng);
+                let hash = BlockIdHash::Poseidon;
+                OptimizedElGamalEncryptionCircuit { ct: [c1, c2], hek, bid: hash.block_id(&message), hdk, hash }
+            })
+            .collect();
+
+        let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(circuits[0].clone(), &mut rng).unwrap();
+        let mut proofs = Vec::new();
+        let mut inputs = Vec::new();
+        for circuit in circuits {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit.clone().generate_constraints(cs.clone()).unwrap();
+            inputs.push(cs.borrow().unwrap().instance_assignment[1..].to_vec());
+            proofs.push(Groth16::<Bls12_381>::prove(&pk, circuit, &mut rng).unwrap());
+        }
+        (prepare_verifying_key(&vk), proofs, inputs)
+    }
+
+    #[test]
+    fn test_batch_verification() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (pvk, proofs, inputs) = prove_blocks(4);
+
+        assert_eq!(batch_verify(&pvk, &proofs, &inputs, &mut rng), Ok(()));
+        assert_eq!(batch_verify(&pvk, &proofs[..1], &inputs[..1], &mut rng), Ok(()));
+        assert_eq!(batch_verify(&pvk, &[], &[], &mut rng), Ok(()));
+
+        // Proofs swapped between blocks
+        let mut swapped = proofs.clone();
+        swapped.swap(1, 3);
+        assert_eq!(
+            batch_verify(&pvk, &swapped, &inputs, &mut rng),
+            Err(BatchVerifyError::InvalidProofs(vec![1, 3]))
+        );
+
+        // A tampered ciphertext in one block's public inputs
+        let mut tampered = inputs.clone();
+        tampered[2][1] += Fr::from(1u64);
+        assert_eq!(
+            batch_verify(&pvk, &proofs, &tampered, &mut rng),
+            Err(BatchVerifyError::InvalidProofs(vec![2]))
+        );
+
+        let mut short = inputs.clone();
+        short[0].pop();
+        assert_eq!(batch_verify(&pvk, &proofs, &short, &mut rng), Err(BatchVerifyError::WrongInputCount { index: 0 }));
+    }
+
+    #[test]
+    fn test_invalid_proofs_do_not_cancel_out() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let (pvk, proofs, inputs) = prove_blocks(2);
+
+        // Shifting C by +P in one proof and -P in the other cancels in an unweighted sum
+        let shift = (proofs[0].c.into_group() - proofs[1].c.into_group()).into_affine();
+        let mut forged = proofs.clone();
+        forged[0].c = (forged[0].c + shift).into_affine();
+        forged[1].c = (forged[1].c - shift).into_affine();
+        assert_eq!(
+            batch_verify(&pvk, &forged, &inputs, &mut rng),
+            Err(BatchVerifyError::InvalidProofs(vec![0, 1]))
+        );
+    }
+}
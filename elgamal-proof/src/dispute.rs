@@ -0,0 +1,117 @@
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::pairing::Pairing;
+use ark_ff::{PrimeField, ToConstraintField};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
+use ark_std::rand::Rng;
+use hash_gadgets::BlockIdHash;
+use std::fmt;
+
+use crate::dispute_circuit::BlockDisputeCircuit;
+use crate::elgamal::{decrypt_elgamal, setup_elgamal};
+
+/// Why a buyer cannot dispute a block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DisputeError {
+    /// c1^hdk is zero, so the block has no plaintext to hash; no proof of encryption accepts such a ciphertext
+    UndecryptableBlock,
+    /// The block decrypts to a message hashing to its block ID, so there is nothing to dispute
+    BlockMatchesId,
+}
+
+impl fmt::Display for DisputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisputeError::UndecryptableBlock => write!(f, "block cannot be decrypted under the disclosed key"),
+            DisputeError::BlockMatchesId => write!(f, "block decrypts to its block ID"),
+        }
+    }
+}
+
+impl std::error::Error for DisputeError {}
+
+/// Builds the buyer's dispute witness for a delivered block
+///
+/// Decrypts (c1, c2) under the disclosed key and checks natively that the plaintext really
+/// misses bid, so the buyer never spends a proving run on an honest block.
+pub fn prepare_block_dispute<F: PrimeField + Absorb>(
+    ct: [F; 2],
+    hdk: F,
+    bid: [u8; 32],
+    hash: BlockIdHash,
+) -> Result<BlockDisputeCircuit<F>, DisputeError> {
+    let message = decrypt_elgamal(&hdk, &ct).ok_or(DisputeError::UndecryptableBlock)?;
+    if hash.block_id(&message) == bid {
+        return Err(DisputeError::BlockMatchesId);
+    }
+    Ok(BlockDisputeCircuit { ct, hdk, bid, hash })
+}
+
+/// Public inputs of `BlockDisputeCircuit` in circuit order: c1, c2, hdk, then the block ID
+/// bytes packed little-endian into field elements of 31 bytes each
+pub fn dispute_public_inputs<F: PrimeField>(ct: &[F; 2], hdk: &F, bid: &[u8; 32]) -> Vec<F> {
+    let mut inputs = vec![ct[0], ct[1], *hdk];
+    inputs.extend(ToConstraintField::<F>::to_field_elements(&bid[..]).unwrap());
+    inputs
+}
+
+/// Checks a dispute for the block (c1, c2) committed to bid, whose key hdk the seller disclosed
+///
+/// This is what a refund flow calls before refunding the buyer: every input is already on
+/// record, so a valid proof shows the seller delivered a block that does not match its ID.
+pub fn verify_block_dispute<E: Pairing>(
+    pvk: &PreparedVerifyingKey<E>,
+    ct: &[E::ScalarField; 2],
+    hdk: &E::ScalarField,
+    bid: &[u8; 32],
+    proof: &Proof<E>,
+) -> bool {
+    let public_inputs = dispute_public_inputs(ct, hdk, bid);
+    Groth16::<E>::verify_proof(pvk, proof, &public_inputs).unwrap_or(false)
+}
+
+/// Generates a block whose delivered ciphertext was corrupted after its block ID was committed
+/// Returns (ct, hdk, bid) with bid computed under `hash` from the original message.
+pub fn setup_corrupted_block<F: PrimeField + Absorb, R: Rng>(rng: &mut R, hash: BlockIdHash) -> ([F; 2], F, [u8; 32]) {
+    let (c1, _c2, hdk, message, _hek, _bid) = setup_elgamal::<F, R>(rng);
+    let corrupted_c2 = F::rand(rng);
+    ([c1, corrupted_c2], hdk, hash.block_id(&message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_crypto_primitives::snark::SNARK;
+    use ark_ff::Field;
+    use ark_groth16::prepare_verifying_key;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_refund_flow_verifies_dispute() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let hash = BlockIdHash::Poseidon;
+        let (ct, hdk, bid) = setup_corrupted_block::<Fr, _>(&mut rng, hash);
+        let circuit = prepare_block_dispute(ct, hdk, bid, hash).unwrap();
+
+        let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let proof = Groth16::<Bls12_381>::prove(&pk, circuit, &mut rng).unwrap();
+        assert!(verify_block_dispute(&pvk, &ct, &hdk, &bid, &proof));
+
+        // The proof is bound to the disputed block, key and block ID
+        assert!(!verify_block_dispute(&pvk, &[ct[0], ct[1] + Fr::ONE], &hdk, &bid, &proof));
+        assert!(!verify_block_dispute(&pvk, &ct, &(hdk + Fr::ONE), &bid, &proof));
+        let mut other_bid = bid;
+        other_bid[0] ^= 1;
+        assert!(!verify_block_dispute(&pvk, &ct, &hdk, &other_bid, &proof));
+    }
+
+    #[test]
+    fn test_undecryptable_block() {
+        let hdk = Fr::from(5u64);
+        assert_eq!(
+            prepare_block_dispute([Fr::from(0u64), Fr::ONE], hdk, [0u8; 32], BlockIdHash::Sha256).err(),
+            Some(DisputeError::UndecryptableBlock)
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, prelude::*, uint8::UInt8};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use hash_gadgets::BlockIdHash;
+
+use crate::circuit::OptimizedElGamalEncryptionCircuit;
+
+/// Buyer's fraud proof that a block does not decrypt to its block ID
+///
+/// Shows that (c1, c2) decrypts under the key hdk disclosed by the seller to a message whose
+/// hash is not bid. Every input is public, so the proof only saves the refund flow from doing
+/// the exponentiation and hash on-chain. As in the decryption circuits the key bits are
+/// range-checked: powers only depend on the exponent modulo p - 1, so the bits of hdk + p
+/// would decrypt an honest block to a different message and forge a dispute.
+#[derive(Clone)]
+pub struct BlockDisputeCircuit<F: PrimeField = Fr> {
+    pub ct: [F; 2],        // Ciphertext (c1, c2) the seller delivered
+    pub hdk: F,            // Decryption key the seller disclosed
+    pub bid: [u8; 32],     // Block ID the seller committed to
+    pub hash: BlockIdHash, // Hash function deriving bid from the message
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for BlockDisputeCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Allocate ciphertext, disclosed key and block ID as public inputs
+        let c1_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.ct[0]))?;
+        let c2_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.ct[1]))?;
+        let hdk_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.hdk))?;
+        let bid_var = UInt8::<F>::new_input_vec(cs.clone(), &self.bid)?;
+
+        // m = c2 / c1^hdk with the canonical bits of hdk
+        let hdk_bits = hdk_var.to_bits_le()?;
+        let s = OptimizedElGamalEncryptionCircuit::variable_base_exponentiation(&c1_var, &hdk_bits)?;
+        let m = c2_var * s.inverse()?;
+
+        let m_bytes = OptimizedElGamalEncryptionCircuit::field_to_bytes_optimized(&m)?;
+        let hash_bytes = self.hash.block_id_gadget(&m_bytes)?;
+        Self::enforce_bytes_differ(&hash_bytes[..32], &bid_var)
+    }
+}
+
+impl<F: PrimeField> BlockDisputeCircuit<F> {
+    /// Enforces that two byte strings differ in at least one byte
+    /// Both are packed into field elements the way public input bytes are, so the check costs
+    /// one inequality per packed element instead of one per byte.
+    fn enforce_bytes_differ(left: &[UInt8<F>], right: &[UInt8<F>]) -> Result<(), SynthesisError> {
+        assert_eq!(left.len(), right.len());
+        let chunk_size = (F::MODULUS_BIT_SIZE as usize - 1) / 8;
+        let mut differs = Vec::new();
+        for (left, right) in left.chunks(chunk_size).zip(right.chunks(chunk_size)) {
+            let left = Boolean::le_bits_to_fp(&left.to_bits_le()?)?;
+            let right = Boolean::le_bits_to_fp(&right.to_bits_le()?)?;
+            differs.push(left.is_neq(&right)?);
+        }
+        Boolean::kary_or(&differs)?.enforce_equal(&Boolean::TRUE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispute::{prepare_block_dispute, DisputeError};
+    use crate::elgamal::setup_elgamal;
+    use ark_ff::Field;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    fn is_satisfied(circuit: BlockDisputeCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_dispute_of_corrupted_block() {
+        let mut rng = test_rng();
+        let (c1, c2, hdk, _message, _hek, bid) = setup_elgamal::<Fr, _>(&mut rng);
+
+        // A corrupted ciphertext and a wrong disclosed key can both be disputed
+        let corrupted = prepare_block_dispute([c1, c2 + Fr::ONE], hdk, bid, BlockIdHash::Sha256).unwrap();
+        assert!(is_satisfied(corrupted));
+        let wrong_key = prepare_block_dispute([c1, c2], hdk + Fr::ONE, bid, BlockIdHash::Sha256).unwrap();
+        assert!(is_satisfied(wrong_key));
+
+        // A block ID computed with another hash does not match either
+        let keccak = prepare_block_dispute([c1, c2], hdk, bid, BlockIdHash::Keccak256).unwrap();
+        assert!(is_satisfied(keccak));
+    }
+
+    #[test]
+    fn test_honest_block_cannot_be_disputed() {
+        let mut rng = test_rng();
+        let (c1, c2, hdk, _message, _hek, bid) = setup_elgamal::<Fr, _>(&mut rng);
+        assert_eq!(
+            prepare_block_dispute([c1, c2], hdk, bid, BlockIdHash::Sha256).err(),
+            Some(DisputeError::BlockMatchesId)
+        );
+
+        let honest = BlockDisputeCircuit { ct: [c1, c2], hdk, bid, hash: BlockIdHash::Sha256 };
+        assert!(!is_satisfied(honest));
+    }
+}
@@ -0,0 +1,179 @@
+use ark_bls12_381::Fr;
+use ark_ec::AffineRepr;
+use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsAffine, EdwardsProjective, Fr as JubjubScalar};
+use ark_ff::{AdditiveGroup, BigInteger, PrimeField};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*, uint8::UInt8};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::vec::Vec;
+use hash_gadgets::BlockIdHash;
+
+use crate::circuit::OptimizedElGamalEncryptionCircuit;
+
+/// EC-ElGamal decryption proof over Jubjub, the twisted Edwards curve embedded in BLS12-381 `Fr`.
+///
+/// Curve arithmetic on Jubjub is native field arithmetic in the proving field, so the
+/// decryption `m = c2 - (hdk * c1).x` costs one variable-base scalar multiplication.
+/// `c1` is constrained to Jubjub's prime-order subgroup, so the shared secret only depends
+/// on `hdk` modulo the subgroup order and not on the bits the prover picks to represent it.
+#[derive(Clone)]
+pub struct JubjubElGamalEncryptionCircuit {
+    pub ct: (EdwardsAffine, Fr), // Ciphertext (c1, c2)
+    pub hek: EdwardsAffine,      // Public key (hek = hdk * G)
+    pub bid: [u8; 32],           // Block ID as a hash of the message
+    pub hdk: JubjubScalar,       // Private key (Hierarchical Derived Key)
+    pub hash: BlockIdHash,       // Hash function deriving bid from the message
+}
+
+impl ConstraintSynthesizer<Fr> for JubjubElGamalEncryptionCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        // Allocate ciphertext as public inputs
+        let c1_var = EdwardsVar::new_input(cs.clone(), || Ok(self.ct.0))?;
+        Self::enforce_prime_order(cs.clone(), &c1_var, self.ct.0)?;
+        let c2_var = FpVar::<Fr>::new_input(cs.clone(), || Ok(self.ct.1))?;
+
+        // Allocate the buyer's published public key as public input
+        let hek_var = EdwardsVar::new_input(cs.clone(), || Ok(self.hek))?;
+
+        // Allocate block ID as public input
+        let bid_var = UInt8::<Fr>::new_input_vec(cs.clone(), &self.bid)?;
+
+        // Allocate private key bits as witness; scalars live in Jubjub's (smaller) scalar field
+        let hdk_bits = Vec::<Boolean<Fr>>::new_witness(cs.clone(), || {
+            Ok(self.hdk.into_bigint().to_bits_le()[..JubjubScalar::MODULUS_BIT_SIZE as usize].to_vec())
+        })?;
+
+        // Key binding: hek = hdk * G, using precomputed multiples of the fixed generator
+        let generator_multiples = Self::generator_multiples(hdk_bits.len());
+        let mut expected_hek = EdwardsVar::zero();
+        expected_hek.precomputed_base_scalar_mul_le(hdk_bits.iter().zip(generator_multiples.iter()))?;
+        expected_hek.enforce_equal(&hek_var)?;
+
+        // Shared secret: s = hdk * c1
+        let s = c1_var.scalar_mul_le(hdk_bits.iter())?;
+
+        // EC-ElGamal decryption: m = c2 - s.x
+        let m = &c2_var - &s.x;
+
+        // Hash the decrypted message and compare with the block ID
+        let m_bytes = OptimizedElGamalEncryptionCircuit::field_to_bytes_optimized(&m)?;
+        let hash_bytes = self.hash.block_id_gadget(&m_bytes)?;
+        OptimizedElGamalEncryptionCircuit::batch_equality_check(&hash_bytes[..32], &bid_var)?;
+
+        Ok(())
+    }
+}
+
+impl JubjubElGamalEncryptionCircuit {
+    /// Enforces that a public point lies in Jubjub's prime-order subgroup
+    /// Inputs are only checked to be on the curve, whereas a witness is allocated as
+    /// 8 * (8^-1 * point), so equating the two rules out any small-order component.
+    pub(crate) fn enforce_prime_order(
+        cs: ConstraintSystemRef<Fr>,
+        point_var: &EdwardsVar,
+        point: EdwardsAffine,
+    ) -> Result<(), SynthesisError> {
+        let subgroup_var = EdwardsVar::new_witness(cs, || Ok(point))?;
+        subgroup_var.enforce_equal(point_var)
+    }
+
+    /// Powers-of-two multiples [G, 2G, 4G, ...] of the Jubjub generator
+    pub(crate) fn generator_multiples(num_bits: usize) -> Vec<EdwardsProjective> {
+        let mut multiples = Vec::with_capacity(num_bits);
+        let mut current = EdwardsAffine::generator().into_group();
+        for _ in 0..num_bits {
+            multiples.push(current);
+            current.double_in_place();
+        }
+        multiples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elgamal::setup_ec_elgamal;
+    use ark_ec::CurveGroup;
+    use ark_ff::{Field, UniformRand};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_jubjub_circuit_correctness() {
+        let mut rng = test_rng();
+        let (c1, c2, hdk, _message, hek, bid) = setup_ec_elgamal(&mut rng);
+
+        let circuit = JubjubElGamalEncryptionCircuit {
+            ct: (c1, c2),
+            hek,
+            bid,
+            hdk,
+            hash: BlockIdHash::Sha256,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        println!("Jubjub constraints: {}", cs.num_constraints());
+        assert!(cs.is_satisfied().unwrap(), "Jubjub circuit should be satisfied");
+    }
+
+    #[test]
+    fn test_jubjub_circuit_wrong_key() {
+        let mut rng = test_rng();
+        let (c1, c2, _hdk, _message, hek, bid) = setup_ec_elgamal(&mut rng);
+
+        let circuit = JubjubElGamalEncryptionCircuit {
+            ct: (c1, c2),
+            hek,
+            bid,
+            hdk: JubjubScalar::rand(&mut rng),
+            hash: BlockIdHash::Sha256,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap(), "Wrong key must not satisfy the circuit");
+    }
+
+    #[test]
+    fn test_jubjub_circuit_rejects_other_public_key() {
+        let mut rng = test_rng();
+        let (c1, c2, hdk, _message, _hek, bid) = setup_ec_elgamal(&mut rng);
+        let (_, _, _, _, other_hek, _) = setup_ec_elgamal(&mut rng);
+
+        let circuit = JubjubElGamalEncryptionCircuit {
+            ct: (c1, c2),
+            hek: other_hek,
+            bid,
+            hdk,
+            hash: BlockIdHash::Sha256,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap(), "Proof must be bound to the published public key");
+    }
+
+    #[test]
+    fn test_jubjub_circuit_rejects_small_order_c1() {
+        let mut rng = test_rng();
+        let (c1, _c2, hdk, message, hek, bid) = setup_ec_elgamal(&mut rng);
+
+        // Add the point of order 2 to c1 and recompute c2 so the ciphertext still decrypts
+        // to the message under hdk's bits
+        let order_two = EdwardsAffine::new_unchecked(Fr::ZERO, -Fr::ONE);
+        let tampered_c1 = (c1 + order_two).into_affine();
+        let tampered_c2 = message + (tampered_c1 * hdk).into_affine().x;
+
+        let circuit = JubjubElGamalEncryptionCircuit {
+            ct: (tampered_c1, tampered_c2),
+            hek,
+            bid,
+            hdk,
+            hash: BlockIdHash::Sha256,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap(), "c1 outside the prime-order subgroup must be rejected");
+    }
+}
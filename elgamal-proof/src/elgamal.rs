@@ -0,0 +1,293 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ed_on_bls12_381::{EdwardsAffine, Fr as JubjubScalar};
+use ark_ff::{BigInteger, PrimeField, UniformRand};
+use ark_std::rand::Rng;
+use hash_gadgets::{keccak::keccak256, poseidon::{poseidon_hash, poseidon_squeeze}, BlockIdHash};
+
+/// Generator of the ElGamal group: the multiplicative generator of the whole of `F*`
+///
+/// A generator of a proper subgroup does not bind the key. 2 is a quadratic residue in both
+/// BLS12-381 and BN254 `Fr`, so hdk' = hdk + (p - 1) / 2 gives the same hek = 2^hdk', while
+/// c1^hdk' = -c1^hdk for every non-residue c1.
+pub fn elgamal_generator<F: PrimeField>() -> F {
+    F::GENERATOR
+}
+
+/// Helper function to compute base^exponent in the field
+/// This is a simplified implementation - in practice you'd want more efficient exponentiation
+pub fn compute_power<F: PrimeField>(base: &F, exponent: &F) -> F {
+    // Convert exponent to bits and use square-and-multiply
+    let exp_bits = exponent.into_bigint().to_bits_le();
+    let mut result = F::from(1u64);
+    let mut current_base = *base;
+
+    for bit in exp_bits {
+        if bit {
+            result *= current_base;
+        }
+        current_base = current_base.square();
+    }
+
+    result
+}
+
+/// Computes the block ID of a message as the SHA256 hash of its little-endian bytes
+/// Use `BlockIdHash::block_id` directly for Keccak-256 block IDs
+pub fn compute_block_id<F: PrimeField + Absorb>(message: &F) -> [u8; 32] {
+    BlockIdHash::Sha256.block_id(message)
+}
+
+/// Computes the record ID exactly as AccessControlContract does:
+/// keccak256(abi.encodePacked(blockIDs)) over the concatenated 32-byte block IDs
+pub fn compute_record_id(bids: &[[u8; 32]]) -> [u8; 32] {
+    keccak256(&bids.concat())
+}
+
+/// Generates ElGamal parameters and encrypts a message
+/// Works over the scalar field `F` of any proving curve, e.g. BLS12-381 or BN254
+pub fn setup_elgamal<F: PrimeField + Absorb, R: Rng>(rng: &mut R) -> (F, F, F, F, F, [u8; 32]) {
+    let h2 = elgamal_generator::<F>(); // Generator
+    let hdk = F::rand(rng); // Private key
+    let message = F::rand(rng); // Random message to encrypt
+
+    // Compute public key: hek = h2^hdk mod p1
+    let hek = compute_power(&h2, &hdk);
+
+    // ElGamal encryption
+    let y = F::rand(rng); // Random nonce
+    let (c1, c2) = encrypt_elgamal(&hek, &message, &y);
+
+    // Verify decryption works correctly
+    let decrypted_s = compute_power(&c1, &hdk); // s = c1^hdk mod p1
+    let decrypted_message = c2 * decrypted_s.inverse().unwrap(); // m = c2 * s^(-1) mod p1
+    assert_eq!(message, decrypted_message, "Decryption failed - implementation error!");
+
+    // Compute block ID as SHA256 hash of the original message
+    let bid = compute_block_id(&message);
+
+    (c1, c2, hdk, message, hek, bid)
+}
+
+/// ElGamal encryption of `message` under public key `hek` with nonce `y`
+pub fn encrypt_elgamal<F: PrimeField>(hek: &F, message: &F, y: &F) -> (F, F) {
+    let h2 = elgamal_generator::<F>(); // Generator
+    let c1 = compute_power(&h2, y); // c1 = h2^y mod p1
+    let s = compute_power(hek, y); // s = hek^y mod p1 (shared secret)
+    let c2 = *message * s; // c2 = m * s mod p1
+    (c1, c2)
+}
+
+/// ElGamal decryption of (c1, c2) under `hdk`, or `None` if c1^hdk is zero
+pub fn decrypt_elgamal<F: PrimeField>(hdk: &F, ct: &[F; 2]) -> Option<F> {
+    let s = compute_power(&ct[0], hdk); // s = c1^hdk mod p1
+    Some(ct[1] * s.inverse()?) // m = c2 * s^(-1) mod p1
+}
+
+/// Generates the seller's witness for the proof of correct encryption
+///
+/// Key material, message and block ID come from `setup_elgamal`; the message is then
+/// encrypted under a fresh nonce `y`, which the seller keeps as the witness instead of `hdk`.
+/// Returns (c1, c2, y, message, hek, bid).
+pub fn setup_encryption_proof<F: PrimeField + Absorb, R: Rng>(rng: &mut R) -> (F, F, F, F, F, [u8; 32]) {
+    let (_, _, _hdk, message, hek, bid) = setup_elgamal(rng);
+
+    let y = F::rand(rng); // Random nonce
+    let (c1, c2) = encrypt_elgamal(&hek, &message, &y);
+
+    (c1, c2, y, message, hek, bid)
+}
+
+/// A block re-encrypted to the buyer: (ct, hdk, hek, buyer_ct, y, buyer_pk, message, bid)
+pub type ElGamalReEncryption<F = Fr> = ([F; 2], F, F, [F; 2], F, F, F, [u8; 32]);
+
+/// Re-encrypts the plaintext of `ct` under the buyer's public key with a fresh nonce `y`
+/// The seller decrypts with `hdk` first, so the plaintext never leaves the seller.
+/// Returns `None` if `ct` does not decrypt under `hdk`, as for `decrypt_elgamal`.
+pub fn reencrypt_elgamal<F: PrimeField>(hdk: &F, ct: &[F; 2], buyer_pk: &F, y: &F) -> Option<(F, F)> {
+    let message = decrypt_elgamal(hdk, ct)?;
+    Some(encrypt_elgamal(buyer_pk, &message, y))
+}
+
+/// Generates the seller's witness for the proof of correct re-encryption
+///
+/// The block is encrypted under the seller's key as by `setup_elgamal`, then re-encrypted
+/// under a fresh buyer key pair, the buyer's key being the `publicKey` of AccessControlContract.
+pub fn setup_reencryption_proof<F: PrimeField + Absorb, R: Rng>(rng: &mut R) -> ElGamalReEncryption<F> {
+    let (c1, c2, hdk, message, hek, bid) = setup_elgamal(rng);
+    let (_, _, buyer_sk, _, buyer_pk, _) = setup_elgamal::<F, _>(rng);
+
+    let y = F::rand(rng); // Random nonce
+    let (d1, d2) = reencrypt_elgamal(&hdk, &[c1, c2], &buyer_pk, &y).expect("A fresh ciphertext always decrypts");
+
+    // Verify the buyer can decrypt the new ciphertext
+    assert_eq!(decrypt_elgamal(&buyer_sk, &[d1, d2]), Some(message), "Re-encryption failed - implementation error!");
+
+    ([c1, c2], hdk, hek, [d1, d2], y, buyer_pk, message, bid)
+}
+
+/// A multi-block record encrypted under one key: (cts, hdk, messages, hek, bids)
+pub type ElGamalRecord<const N: usize, F = Fr> = ([[F; 2]; N], F, [F; N], F, [[u8; 32]; N]);
+
+/// Generates one key pair and encrypts N random messages under it, as for a multi-block record
+/// Blocks are returned in record order.
+pub fn setup_record_elgamal<F: PrimeField + Absorb, R: Rng, const N: usize>(rng: &mut R) -> ElGamalRecord<N, F> {
+    // The first block is the one encrypted by setup_elgamal
+    let (c1, c2, hdk, message, hek, bid) = setup_elgamal(rng);
+    let mut cts = [[c1, c2]; N];
+    let mut messages = [message; N];
+    let mut bids = [bid; N];
+
+    for ((ct, message), bid) in cts.iter_mut().zip(messages.iter_mut()).zip(bids.iter_mut()).skip(1) {
+        *message = F::rand(rng);
+        let y = F::rand(rng); // Fresh nonce per block
+        let (c1, c2) = encrypt_elgamal(&hek, message, &y);
+        *ct = [c1, c2];
+        *bid = compute_block_id(message);
+    }
+
+    (cts, hdk, messages, hek, bids)
+}
+
+/// EC-ElGamal encryption over Jubjub of `message` under public key `hek` with nonce `y`
+pub fn encrypt_ec_elgamal(hek: &EdwardsAffine, message: &Fr, y: &JubjubScalar) -> (EdwardsAffine, Fr) {
+    let c1 = (EdwardsAffine::generator() * y).into_affine(); // c1 = y * G
+    let s = (*hek * y).into_affine(); // s = y * hek (shared secret)
+    let c2 = *message + s.x; // c2 = m + s.x
+    (c1, c2)
+}
+
+/// Generates EC-ElGamal parameters over Jubjub and encrypts a message
+///
+/// Jubjub's base field is BLS12-381 `Fr`, so the message stays an `Fr` element and is
+/// masked with the x-coordinate of the shared point: (c1, c2) = (y*G, m + (y*hek).x).
+pub fn setup_ec_elgamal<R: Rng>(
+    rng: &mut R,
+) -> (EdwardsAffine, Fr, JubjubScalar, Fr, EdwardsAffine, [u8; 32]) {
+    let g = EdwardsAffine::generator(); // Prime-order subgroup generator
+    let hdk = JubjubScalar::rand(rng); // Private key
+    let message = Fr::rand(rng); // Random message to encrypt
+
+    // Compute public key: hek = hdk * G
+    let hek = (g * hdk).into_affine();
+
+    // EC-ElGamal encryption
+    let y = JubjubScalar::rand(rng); // Random nonce
+    let (c1, c2) = encrypt_ec_elgamal(&hek, &message, &y);
+
+    // Verify decryption works correctly
+    let decrypted_s = (c1 * hdk).into_affine(); // s = hdk * c1
+    let decrypted_message = c2 - decrypted_s.x; // m = c2 - s.x
+    assert_eq!(message, decrypted_message, "Decryption failed - implementation error!");
+
+    // Compute block ID as SHA256 hash of the original message
+    let bid = compute_block_id(&message);
+
+    (c1, c2, hdk, message, hek, bid)
+}
+
+/// Hybrid (KEM/DEM) encryption of a byte block of at most `MAX_BYTES` bytes
+///
+/// ElGamal encapsulates a fresh symmetric key k. The block is packed into field elements
+/// and every element is masked with the Poseidon keystream squeezed from k, so the body
+/// always has `hybrid_body_len::<F>(MAX_BYTES)` elements whatever the plaintext length.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HybridCiphertext<F: PrimeField = Fr> {
+    pub kem: [F; 2],  // ElGamal encapsulation (c1, c2) of the symmetric key
+    pub body: Vec<F>, // Packed block plus keystream, zero-padded to the maximum length
+    pub len: usize,   // Plaintext length in bytes
+}
+
+/// A hybrid-encrypted random block: (ct, hdk, block, hek, bid)
+pub type HybridBlock<F = Fr> = (HybridCiphertext<F>, F, Vec<u8>, F, [u8; 32]);
+
+/// Whole bytes packed into each body element; they always fit below the modulus of `F`
+pub fn hybrid_bytes_per_element<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize - 1) / 8
+}
+
+/// Number of body elements of a hybrid block of up to `max_bytes` bytes
+pub fn hybrid_body_len<F: PrimeField>(max_bytes: usize) -> usize {
+    max_bytes.div_ceil(hybrid_bytes_per_element::<F>())
+}
+
+/// Packs a block into little-endian field elements, zero-padded to `MAX_BYTES`
+pub fn pack_hybrid_block<F: PrimeField, const MAX_BYTES: usize>(block: &[u8]) -> Vec<F> {
+    assert!(block.len() <= MAX_BYTES, "block of {} bytes exceeds the maximum of {}", block.len(), MAX_BYTES);
+    let mut elements: Vec<F> = block
+        .chunks(hybrid_bytes_per_element::<F>())
+        .map(F::from_le_bytes_mod_order)
+        .collect();
+    elements.resize(hybrid_body_len::<F>(MAX_BYTES), F::ZERO);
+    elements
+}
+
+/// Block ID of a hybrid block: the Poseidon hash of its byte length and packed elements
+///
+/// The length is hashed with the elements, so blocks that differ only in trailing zero
+/// bytes get different IDs.
+pub fn compute_hybrid_block_id<F: PrimeField + Absorb, const MAX_BYTES: usize>(block: &[u8]) -> [u8; 32] {
+    let mut inputs = vec![F::from(block.len() as u64)];
+    inputs.extend(pack_hybrid_block::<F, MAX_BYTES>(block));
+    let mut digest = poseidon_hash(&inputs).into_bigint().to_bytes_le();
+    digest.resize(32, 0);
+    digest[..32].try_into().unwrap()
+}
+
+/// Encrypts a block under `hek` with a fresh symmetric key and ElGamal nonce
+pub fn encrypt_hybrid_block<F: PrimeField + Absorb, R: Rng, const MAX_BYTES: usize>(
+    hek: &F,
+    block: &[u8],
+    rng: &mut R,
+) -> HybridCiphertext<F> {
+    let key = F::rand(rng); // Symmetric key
+    let y = F::rand(rng); // Random nonce
+    let (c1, c2) = encrypt_elgamal(hek, &key, &y);
+
+    let packed = pack_hybrid_block::<F, MAX_BYTES>(block);
+    let keystream = poseidon_squeeze(&[key], packed.len());
+    let body = packed.iter().zip(keystream.iter()).map(|(m, k)| *m + k).collect();
+
+    HybridCiphertext { kem: [c1, c2], body, len: block.len() }
+}
+
+/// Decrypts the packed body elements of a hybrid ciphertext, including the zero padding,
+/// or `None` if the KEM does not decrypt because c1^hdk is zero
+pub fn decrypt_hybrid_elements<F: PrimeField + Absorb>(hdk: &F, ct: &HybridCiphertext<F>) -> Option<Vec<F>> {
+    let key = decrypt_elgamal(hdk, &ct.kem)?; // k = c2 * (c1^hdk)^(-1)
+    let keystream = poseidon_squeeze(&[key], ct.body.len());
+    Some(ct.body.iter().zip(keystream.iter()).map(|(c, k)| *c - k).collect())
+}
+
+/// Decrypts a hybrid ciphertext back to its plaintext bytes, or `None` if its KEM does not decrypt
+pub fn decrypt_hybrid_block<F: PrimeField + Absorb>(hdk: &F, ct: &HybridCiphertext<F>) -> Option<Vec<u8>> {
+    let mut block: Vec<u8> = decrypt_hybrid_elements(hdk, ct)?
+        .iter()
+        .flat_map(|m| {
+            let mut bytes = m.into_bigint().to_bytes_le();
+            bytes.resize(hybrid_bytes_per_element::<F>(), 0);
+            bytes
+        })
+        .collect();
+    block.truncate(ct.len);
+    Some(block)
+}
+
+/// Generates an ElGamal key pair and hybrid-encrypts a random block of `len` bytes
+pub fn setup_hybrid_elgamal<F: PrimeField + Absorb, R: Rng, const MAX_BYTES: usize>(
+    rng: &mut R,
+    len: usize,
+) -> HybridBlock<F> {
+    let (_, _, hdk, _, hek, _) = setup_elgamal::<F, _>(rng);
+
+    let mut block = vec![0u8; len];
+    rng.fill_bytes(&mut block);
+    let ct = encrypt_hybrid_block::<F, _, MAX_BYTES>(&hek, &block, rng);
+
+    // Verify decryption works correctly
+    assert_eq!(decrypt_hybrid_block(&hdk, &ct), Some(block.clone()), "Decryption failed - implementation error!");
+
+    let bid = compute_hybrid_block_id::<F, MAX_BYTES>(&block);
+    (ct, hdk, block, hek, bid)
+}
@@ -0,0 +1,228 @@
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::vec::Vec;
+use hash_gadgets::BlockIdHash;
+use std::fmt;
+use std::io::{self, Read};
+
+/// Marker byte appended to every file before zero padding (ISO/IEC 7816-4 padding)
+///
+/// The marker is always present, even when the file already fills its last block, so the
+/// padded length never depends on the file's own trailing bytes and decoding is unambiguous.
+pub const PADDING_MARKER: u8 = 0x80;
+
+/// Why a sequence of messages is not the encoding of any file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Message `index` has bits set above the bytes a block carries
+    NonCanonicalBlock { index: usize },
+    /// The last message does not end in the padding marker followed by zeros
+    MissingPadding,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::NonCanonicalBlock { index } => write!(f, "block {} is not a canonical byte encoding", index),
+            DecodeError::MissingPadding => write!(f, "the last block does not carry the padding marker"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// File bytes carried by each message; every block value plus one is at most 2^(8 * bytes) < p
+pub fn bytes_per_block<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize - 1) / 8
+}
+
+/// Splits a file into padded blocks and encodes each one as a message for `setup_elgamal`
+///
+/// Block i holds bytes [i * B, (i + 1) * B) of the padded file as a little-endian integer plus one,
+/// where B = `bytes_per_block::<F>()`. Distinct files always give distinct message sequences.
+///
+/// The offset keeps every message in F*: multiplicative ElGamal encrypts zero as c2 = 0 whatever
+/// the nonce, which would reveal every all-zero block of the file.
+pub fn encode_file<F: PrimeField>(data: &[u8]) -> Vec<F> {
+    let block_bytes = bytes_per_block::<F>();
+    let mut padded = data.to_vec();
+    padded.push(PADDING_MARKER);
+    padded.resize(padded.len().div_ceil(block_bytes) * block_bytes, 0);
+
+    padded.chunks(block_bytes).map(|block| F::from_le_bytes_mod_order(block) + F::ONE).collect()
+}
+
+/// Reads a byte stream to the end and encodes it with `encode_file`
+pub fn encode_reader<F: PrimeField, R: Read>(mut reader: R) -> io::Result<Vec<F>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    Ok(encode_file(&data))
+}
+
+/// Inverse of `encode_file`
+pub fn decode_file<F: PrimeField>(messages: &[F]) -> Result<Vec<u8>, DecodeError> {
+    let block_bytes = bytes_per_block::<F>();
+    let mut padded = Vec::with_capacity(messages.len() * block_bytes);
+    for (index, message) in messages.iter().enumerate() {
+        let bytes = message_bytes_le(&(*message - F::ONE));
+        if bytes[block_bytes..].iter().any(|&b| b != 0) {
+            return Err(DecodeError::NonCanonicalBlock { index });
+        }
+        padded.extend_from_slice(&bytes[..block_bytes]);
+    }
+
+    // Strip the zero padding, then the marker itself
+    let marker = padded.iter().rposition(|&b| b != 0).ok_or(DecodeError::MissingPadding)?;
+    if padded[marker] != PADDING_MARKER || padded.len() - marker > block_bytes {
+        return Err(DecodeError::MissingPadding);
+    }
+    padded.truncate(marker);
+    Ok(padded)
+}
+
+/// Little-endian bytes of a message, laid out exactly as `field_to_bytes_optimized` packs
+/// its bits in the circuit: ceil(MODULUS_BIT_SIZE / 8) bytes, the last one zero-extended
+pub fn message_bytes_le<F: PrimeField>(message: &F) -> Vec<u8> {
+    let mut bytes = message.into_bigint().to_bytes_le();
+    bytes.resize((F::MODULUS_BIT_SIZE as usize).div_ceil(8), 0);
+    bytes
+}
+
+/// Block IDs of the encoded messages as the decryption circuits over `F` compute them
+pub fn compute_file_block_ids<F: PrimeField + Absorb>(messages: &[F], hash: BlockIdHash) -> Vec<[u8; 32]> {
+    messages
+        .iter()
+        .map(|message| hash.hash_le_bytes::<F>(&message_bytes_le(message)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::OptimizedElGamalEncryptionCircuit;
+    use crate::elgamal::{compute_power, encrypt_elgamal, setup_elgamal};
+    use ark_bls12_381::Fr;
+    use ark_ff::{Field, Zero};
+    use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::{rngs::StdRng, Rng, SeedableRng};
+    use ark_std::UniformRand;
+
+    fn random_file(rng: &mut StdRng, len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        rng.fill(&mut data[..]);
+        data
+    }
+
+    #[test]
+    fn test_round_trip_random_files() {
+        let mut rng = StdRng::seed_from_u64(14);
+        let block_bytes = bytes_per_block::<Fr>();
+        let mut lengths = vec![0, 1, block_bytes - 1, block_bytes, block_bytes + 1, 4096];
+        lengths.extend((0..20).map(|_| rng.gen_range(0..2000)));
+
+        for len in lengths {
+            let data = random_file(&mut rng, len);
+            let messages = encode_file::<Fr>(&data);
+            assert_eq!(messages.len(), (len + 1).div_ceil(block_bytes), "length {}", len);
+            assert_eq!(decode_file(&messages).unwrap(), data, "length {}", len);
+        }
+    }
+
+    #[test]
+    fn test_encoding_is_injective_on_trailing_zeros() {
+        // Files that differ only in trailing zero bytes must not share an encoding
+        let mut encodings: Vec<Vec<Fr>> = (0..=64).map(|len| encode_file(&vec![0u8; len])).collect();
+        encodings.dedup();
+        assert_eq!(encodings.len(), 65);
+
+        let with_marker = encode_file::<Fr>(&[1, PADDING_MARKER]);
+        assert_ne!(with_marker, encode_file::<Fr>(&[1]));
+        assert_eq!(decode_file(&with_marker).unwrap(), vec![1, PADDING_MARKER]);
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_messages() {
+        let block_bytes = bytes_per_block::<Fr>();
+        let mut messages = encode_file::<Fr>(b"record block");
+
+        assert_eq!(decode_file::<Fr>(&[]), Err(DecodeError::MissingPadding));
+        assert_eq!(decode_file(&[Fr::from(1u64)]), Err(DecodeError::MissingPadding));
+        assert_eq!(decode_file(&[Fr::from(0x4142u64)]), Err(DecodeError::MissingPadding));
+
+        // Zero is outside the encoding's range, since every block is offset by one
+        assert_eq!(decode_file(&[Fr::from(0u64)]), Err(DecodeError::NonCanonicalBlock { index: 0 }));
+
+        // A whole zero block after the marker is padding the encoder never produces
+        let mut extra_block = messages.clone();
+        extra_block.push(Fr::from(1u64));
+        assert_eq!(decode_file(&extra_block), Err(DecodeError::MissingPadding));
+
+        // A random field element carries more than bytes_per_block bytes
+        messages.insert(0, Fr::from(2u64) + Fr::from(2u64).pow([8 * block_bytes as u64]));
+        assert_eq!(decode_file(&messages), Err(DecodeError::NonCanonicalBlock { index: 0 }));
+    }
+
+    #[test]
+    fn test_reader_and_bn254_encoding() {
+        let mut rng = StdRng::seed_from_u64(254);
+        let data = random_file(&mut rng, 777);
+
+        let messages = encode_reader::<Fr, _>(&data[..]).unwrap();
+        assert_eq!(messages, encode_file::<Fr>(&data));
+
+        let bn_messages = encode_file::<ark_bn254::Fr>(&data);
+        assert_eq!(decode_file(&bn_messages).unwrap(), data);
+    }
+
+    #[test]
+    fn test_block_ids_match_circuit_layout() {
+        let mut rng = StdRng::seed_from_u64(32);
+        let data = random_file(&mut rng, 100);
+        let messages = encode_file::<Fr>(&data);
+
+        for hash in [BlockIdHash::Sha256, BlockIdHash::Keccak256, BlockIdHash::Poseidon] {
+            let bids = compute_file_block_ids(&messages, hash);
+            for (message, bid) in messages.iter().zip(bids.iter()) {
+                let cs = ConstraintSystem::<Fr>::new_ref();
+                let m_var = FpVar::<Fr>::new_witness(cs.clone(), || Ok(*message)).unwrap();
+                let m_bytes = OptimizedElGamalEncryptionCircuit::field_to_bytes_optimized(&m_var).unwrap();
+                let layout: Vec<u8> = m_bytes.iter().map(|b| b.value().unwrap()).collect();
+                assert_eq!(layout, message_bytes_le(message));
+
+                let digest: Vec<u8> = hash.block_id_gadget(&m_bytes).unwrap().iter().map(|b| b.value().unwrap()).collect();
+                assert_eq!(&digest[..32], bid, "{:?}", hash);
+                assert_eq!(*bid, hash.block_id(message), "{:?}", hash);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encrypted_file_round_trip() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let (_, _, hdk, _, hek, _) = setup_elgamal::<Fr, _>(&mut rng);
+        let data = random_file(&mut rng, 300);
+
+        let decrypted: Vec<Fr> = encode_file::<Fr>(&data)
+            .iter()
+            .map(|message| {
+                let (c1, c2) = encrypt_elgamal(&hek, message, &Fr::rand(&mut rng));
+                c2 * compute_power(&c1, &hdk).inverse().unwrap()
+            })
+            .collect();
+        assert_eq!(decode_file(&decrypted).unwrap(), data);
+    }
+
+    #[test]
+    fn test_zero_blocks_encrypt_to_nonzero_ciphertexts() {
+        let mut rng = StdRng::seed_from_u64(64);
+        let (_, _, _, _, hek, _) = setup_elgamal::<Fr, _>(&mut rng);
+
+        let messages = encode_file::<Fr>(&[0u8; 64]);
+        assert!(messages.iter().all(|message| !message.is_zero()));
+        for message in &messages {
+            let (_, c2) = encrypt_elgamal(&hek, message, &Fr::rand(&mut rng));
+            assert!(!c2.is_zero());
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, prelude::*, uint8::UInt8};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use hash_gadgets::{poseidon::PoseidonGadget, BlockIdHash};
+
+use crate::circuit::OptimizedElGamalEncryptionCircuit;
+use crate::hd_key::{COMMIT_TAG, DERIVE_TAG};
+
+/// Decryption proof for a block key derived from a committed master key
+///
+/// Shows that hdk = Poseidon(DERIVE_TAG, master, path) for the master behind the public
+/// commitment, that hek = g^hdk, and that (c1, c2) decrypts under hdk to a message hashing
+/// to bid. Every block of a record is proven against the same commitment, so an auditor can
+/// check that one master key covers the whole record without learning it.
+#[derive(Clone)]
+pub struct DerivedKeyElGamalCircuit<F: PrimeField = Fr> {
+    pub ct: [F; 2],               // Ciphertext (c1, c2)
+    pub hek: F,                   // Public key (hek = g^hdk)
+    pub bid: [u8; 32],            // Block ID as a hash of the message
+    pub master_commitment: F,     // Poseidon(COMMIT_TAG, master)
+    pub path: F,                  // `KeyPath::to_field` of the block index or record ID
+    pub master: F,                // Private master key
+    pub hash: BlockIdHash,        // Hash function deriving bid from the message
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for DerivedKeyElGamalCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Allocate ciphertext, public key and block ID as public inputs
+        let c1_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.ct[0]))?;
+        let c2_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.ct[1]))?;
+        let hek_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.hek))?;
+        let bid_var = UInt8::<F>::new_input_vec(cs.clone(), &self.bid)?;
+
+        // Allocate the master key commitment and derivation path as public inputs
+        let commitment_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.master_commitment))?;
+        let path_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.path))?;
+
+        // Allocate the master key as witness and open the commitment
+        let master_var = FpVar::<F>::new_witness(cs.clone(), || Ok(self.master))?;
+        let commit_tag = FpVar::constant(F::from(COMMIT_TAG));
+        PoseidonGadget::hash(&[commit_tag, master_var.clone()])?.enforce_equal(&commitment_var)?;
+
+        // hdk = Poseidon(DERIVE_TAG, master, path)
+        // The bits are range-checked, so the exponent is exactly the derived field element
+        let derive_tag = FpVar::constant(F::from(DERIVE_TAG));
+        let hdk_var = PoseidonGadget::hash(&[derive_tag, master_var, path_var])?;
+        let hdk_bits = hdk_var.to_bits_le()?;
+
+        // Key binding and decryption as in the single-block circuit
+        OptimizedElGamalEncryptionCircuit::enforce_key_binding(&hek_var, &hdk_bits)?;
+        OptimizedElGamalEncryptionCircuit::enforce_decrypts_to_bid(&c1_var, &c2_var, &hdk_bits, &bid_var, self.hash)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd_key::{setup_derived_elgamal, setup_derived_elgamal_with_master, KeyPath};
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::Rng;
+    use ark_std::test_rng;
+
+    fn circuit_for<R: Rng>(rng: &mut R, master: &Fr, path: KeyPath) -> DerivedKeyElGamalCircuit {
+        let (c1, c2, master, master_commitment, _hdk, _message, hek, bid) =
+            setup_derived_elgamal_with_master(rng, master, &path);
+        DerivedKeyElGamalCircuit {
+            ct: [c1, c2],
+            hek,
+            bid,
+            master_commitment,
+            path: path.to_field(),
+            master,
+            hash: BlockIdHash::Sha256,
+        }
+    }
+
+    fn is_satisfied(circuit: DerivedKeyElGamalCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_derived_key_circuit_correctness() {
+        let mut rng = test_rng();
+        let (c1, c2, master, master_commitment, _hdk, _message, hek, bid) =
+            setup_derived_elgamal::<Fr, _>(&mut rng, &KeyPath::BlockIndex(3));
+        let circuit = DerivedKeyElGamalCircuit {
+            ct: [c1, c2],
+            hek,
+            bid,
+            master_commitment,
+            path: KeyPath::BlockIndex(3).to_field(),
+            master,
+            hash: BlockIdHash::Sha256,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        println!("Derived key constraints: {}", cs.num_constraints());
+        assert!(cs.is_satisfied().unwrap(), "Derived key circuit should be satisfied");
+    }
+
+    #[test]
+    fn test_whole_record_under_one_commitment() {
+        let mut rng = test_rng();
+        let master = Fr::rand(&mut rng);
+        let circuits: Vec<_> = (0..3).map(|i| circuit_for(&mut rng, &master, KeyPath::BlockIndex(i))).collect();
+        let record = circuit_for(&mut rng, &master, KeyPath::RecordId([9u8; 32]));
+
+        assert!(circuits.iter().chain([&record]).all(|c| c.master_commitment == record.master_commitment));
+        for circuit in circuits.into_iter().chain([record]) {
+            assert!(is_satisfied(circuit));
+        }
+    }
+
+    #[test]
+    fn test_derived_key_rejects_wrong_master_or_path() {
+        let mut rng = test_rng();
+        let master = Fr::rand(&mut rng);
+        let honest = circuit_for(&mut rng, &master, KeyPath::BlockIndex(1));
+
+        // A key derived under another master, even with that master's own commitment
+        let other_master = Fr::rand(&mut rng);
+        let other = circuit_for(&mut rng, &other_master, KeyPath::BlockIndex(1));
+
+        let tampered = vec![
+            // Proof claimed for block 2 using block 1's key
+            DerivedKeyElGamalCircuit { path: KeyPath::BlockIndex(2).to_field(), ..honest.clone() },
+            // Right master, but the commitment of another one
+            DerivedKeyElGamalCircuit { master_commitment: other.master_commitment, ..honest.clone() },
+            // Another master's block presented under this record's commitment
+            DerivedKeyElGamalCircuit { master_commitment: honest.master_commitment, ..other.clone() },
+            DerivedKeyElGamalCircuit { master: other_master, ..honest.clone() },
+        ];
+        for circuit in tampered {
+            assert!(!is_satisfied(circuit), "Key not derived from the committed master must be rejected");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_std::rand::Rng;
+use hash_gadgets::poseidon::poseidon_hash;
+
+use crate::elgamal::{compute_block_id, compute_power, elgamal_generator, encrypt_elgamal};
+
+/// Domain tag of the master key commitment
+pub const COMMIT_TAG: u64 = 1;
+/// Domain tag of block key derivation
+pub const DERIVE_TAG: u64 = 2;
+/// Domain tag of record ID paths
+pub const RECORD_TAG: u64 = 3;
+
+/// What a block key is derived for
+///
+/// Both variants map to a single field element, the public `path` of the derivation
+/// circuit, so verifiers recompute it from the block index or record ID they already know.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyPath {
+    /// Position of the block within its record
+    BlockIndex(u64),
+    /// Record ID as stored by AccessControlContract
+    RecordId([u8; 32]),
+}
+
+impl KeyPath {
+    /// Field element the key is derived from
+    /// Record IDs are packed little-endian as 31 + 1 bytes and hashed under `RECORD_TAG`.
+    pub fn to_field<F: PrimeField + Absorb>(&self) -> F {
+        match self {
+            KeyPath::BlockIndex(index) => F::from(*index),
+            KeyPath::RecordId(record_id) => poseidon_hash(&[
+                F::from(RECORD_TAG),
+                F::from_le_bytes_mod_order(&record_id[..31]),
+                F::from(record_id[31]),
+            ]),
+        }
+    }
+}
+
+/// Public commitment to the master key: Poseidon(COMMIT_TAG, master)
+pub fn commit_master_key<F: PrimeField + Absorb>(master: &F) -> F {
+    poseidon_hash(&[F::from(COMMIT_TAG), *master])
+}
+
+/// Block key hdk = Poseidon(DERIVE_TAG, master, path)
+pub fn derive_block_key<F: PrimeField + Absorb>(master: &F, path: &KeyPath) -> F {
+    poseidon_hash(&[F::from(DERIVE_TAG), *master, path.to_field()])
+}
+
+/// A block encrypted under a derived key: (c1, c2, master, commitment, hdk, message, hek, bid)
+pub type DerivedKeyBlock<F = Fr> = (F, F, F, F, F, F, F, [u8; 32]);
+
+/// Samples a master key and encrypts a random message under the key derived for `path`
+pub fn setup_derived_elgamal<F: PrimeField + Absorb, R: Rng>(rng: &mut R, path: &KeyPath) -> DerivedKeyBlock<F> {
+    let master = F::rand(rng); // Master secret
+    setup_derived_elgamal_with_master(rng, &master, path)
+}
+
+/// Encrypts a random message under the key derived from an existing master key
+/// Every block of a record calls this with the same master, so they share one commitment.
+pub fn setup_derived_elgamal_with_master<F: PrimeField + Absorb, R: Rng>(
+    rng: &mut R,
+    master: &F,
+    path: &KeyPath,
+) -> DerivedKeyBlock<F> {
+    let h2 = elgamal_generator::<F>(); // Generator
+    let hdk = derive_block_key(master, path);
+    let hek = compute_power(&h2, &hdk); // hek = h2^hdk
+
+    let message = F::rand(rng); // Random message to encrypt
+    let y = F::rand(rng); // Random nonce
+    let (c1, c2) = encrypt_elgamal(&hek, &message, &y);
+
+    // Verify decryption works correctly
+    let decrypted_s = compute_power(&c1, &hdk);
+    assert_eq!(message, c2 * decrypted_s.inverse().unwrap(), "Decryption failed - implementation error!");
+
+    let bid = compute_block_id(&message);
+    (c1, c2, *master, commit_master_key(master), hdk, message, hek, bid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_block_keys_are_distinct_and_deterministic() {
+        let mut rng = test_rng();
+        let master = Fr::rand(&mut rng);
+        let record_id = [7u8; 32];
+
+        let keys: Vec<Fr> = (0..4).map(|i| derive_block_key(&master, &KeyPath::BlockIndex(i))).collect();
+        assert_eq!(keys[2], derive_block_key(&master, &KeyPath::BlockIndex(2)));
+        for (i, key) in keys.iter().enumerate() {
+            assert!(keys[i + 1..].iter().all(|other| other != key));
+        }
+
+        let record_key = derive_block_key(&master, &KeyPath::RecordId(record_id));
+        assert!(!keys.contains(&record_key));
+        let mut other_record = record_id;
+        other_record[31] ^= 1;
+        assert_ne!(record_key, derive_block_key(&master, &KeyPath::RecordId(other_record)));
+
+        // Another master gives unrelated keys and another commitment
+        let other_master = Fr::rand(&mut rng);
+        assert_ne!(keys[0], derive_block_key(&other_master, &KeyPath::BlockIndex(0)));
+        assert_ne!(commit_master_key(&master), commit_master_key(&other_master));
+    }
+
+    #[test]
+    fn test_commitment_and_key_are_domain_separated() {
+        // Without tags Poseidon(master) and Poseidon(master, 0) absorb to the same state
+        let master = Fr::from(5u64);
+        assert_ne!(commit_master_key(&master), derive_block_key(&master, &KeyPath::BlockIndex(0)));
+        assert_eq!(poseidon_hash(&[master]), poseidon_hash(&[master, Fr::from(0u64)]));
+    }
+}
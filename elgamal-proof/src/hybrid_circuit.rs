@@ -0,0 +1,195 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*, uint8::UInt8};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::vec::Vec;
+use hash_gadgets::poseidon::PoseidonGadget;
+
+use crate::circuit::OptimizedElGamalEncryptionCircuit;
+use crate::elgamal::{decrypt_hybrid_elements, hybrid_body_len, hybrid_bytes_per_element, HybridCiphertext};
+
+/// Hybrid (KEM/DEM) decryption proof for a block of up to `MAX_BYTES` bytes
+///
+/// Decrypts the symmetric key from the ElGamal encapsulation, strips the Poseidon keystream
+/// from every body element and shows that the plaintext hashes to bid. The byte length is a
+/// public input: every byte at or past it must be zero, so the proof covers exactly the
+/// first `len` bytes of the block. The block ID is always `compute_hybrid_block_id`, since a
+/// bit-oriented hash over kilobytes would dominate the circuit.
+#[derive(Clone)]
+pub struct HybridElGamalEncryptionCircuit<const MAX_BYTES: usize, F: PrimeField = Fr> {
+    pub ct: HybridCiphertext<F>, // Encapsulated key, masked body and byte length
+    pub hek: F,                  // Public key (hek = g^hdk)
+    pub bid: [u8; 32],           // Block ID as a Poseidon hash of the length and plaintext
+    pub hdk: F,                  // Private key (Hierarchical Derived Key)
+}
+
+impl<const MAX_BYTES: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for HybridElGamalEncryptionCircuit<MAX_BYTES, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let body_len = hybrid_body_len::<F>(MAX_BYTES);
+        let bytes_per_element = hybrid_bytes_per_element::<F>();
+        assert_eq!(self.ct.body.len(), body_len, "hybrid body must be padded to the maximum block length");
+        assert!(self.ct.len <= MAX_BYTES, "block length exceeds the circuit maximum");
+
+        // Allocate the encapsulated key, masked body and length as public inputs
+        let c1_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.ct.kem[0]))?;
+        let c2_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.ct.kem[1]))?;
+        let body_vars = Vec::<FpVar<F>>::new_input(cs.clone(), || Ok(self.ct.body.clone()))?;
+        let len_var = FpVar::<F>::new_input(cs.clone(), || Ok(F::from(self.ct.len as u64)))?;
+
+        // Allocate the buyer's published public key and the block ID as public inputs
+        let hek_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.hek))?;
+        let bid_var = UInt8::<F>::new_input_vec(cs.clone(), &self.bid)?;
+
+        // Allocate private key bits and bind them to hek = g^hdk
+        let hdk_bits = OptimizedElGamalEncryptionCircuit::alloc_exponent_bits(cs.clone(), self.hdk)?;
+        OptimizedElGamalEncryptionCircuit::enforce_key_binding(&hek_var, &hdk_bits)?;
+
+        // KEM: k = c2 / c1^hdk
+        let s = OptimizedElGamalEncryptionCircuit::variable_base_exponentiation(&c1_var, &hdk_bits)?;
+        let key = &c2_var * s.inverse()?;
+
+        // DEM: m_i = body_i - keystream_i
+        let keystream = PoseidonGadget::squeeze(&[key], body_len)?;
+        let m_vars: Vec<FpVar<F>> = body_vars.iter().zip(keystream.iter()).map(|(c, k)| c - k).collect();
+
+        // Byte j is active exactly when j < len: the flags form a prefix whose sum is len
+        let active = Vec::<Boolean<F>>::new_witness(cs.clone(), || {
+            Ok((0..MAX_BYTES).map(|j| j < self.ct.len).collect::<Vec<_>>())
+        })?;
+        for j in 1..MAX_BYTES {
+            active[j - 1].conditional_enforce_equal(&Boolean::TRUE, &active[j])?;
+        }
+        let active_count = active.iter().fold(FpVar::<F>::zero(), |sum, flag| sum + FpVar::from(flag.clone()));
+        active_count.enforce_equal(&len_var)?;
+
+        // Decompose every plaintext element into bytes; bytes past len must be zero
+        let plaintext_bits = Vec::<Boolean<F>>::new_witness(cs.clone(), || {
+            let plaintext = decrypt_hybrid_elements(&self.hdk, &self.ct).ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(plaintext
+                .iter()
+                .flat_map(|m| m.into_bigint().to_bits_le()[..8 * bytes_per_element].to_vec())
+                .collect::<Vec<_>>())
+        })?;
+        for (i, (m_var, bits)) in m_vars.iter().zip(plaintext_bits.chunks(8 * bytes_per_element)).enumerate() {
+            Boolean::le_bits_to_fp(bits)?.enforce_equal(m_var)?;
+
+            for (b, byte_bits) in bits.chunks(8).enumerate() {
+                let byte = Boolean::le_bits_to_fp(byte_bits)?;
+                match active.get(i * bytes_per_element + b) {
+                    Some(flag) => byte.conditional_enforce_equal(&FpVar::zero(), &!flag)?,
+                    None => byte.enforce_equal(&FpVar::zero())?,
+                }
+            }
+        }
+
+        // bid = Poseidon(len, m_0, .., m_n)
+        let mut hash_inputs = vec![len_var];
+        hash_inputs.extend(m_vars);
+        let digest = PoseidonGadget::hash(&hash_inputs)?;
+        let mut digest_bytes = digest.to_bytes_le()?;
+        digest_bytes.truncate(32);
+        OptimizedElGamalEncryptionCircuit::batch_equality_check(&digest_bytes, &bid_var)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elgamal::{compute_hybrid_block_id, decrypt_hybrid_block, encrypt_hybrid_block, setup_hybrid_elgamal};
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    const MAX_BYTES: usize = 100;
+
+    fn is_satisfied(circuit: HybridElGamalEncryptionCircuit<MAX_BYTES>) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_hybrid_round_trip() {
+        let mut rng = test_rng();
+        for len in [0, 1, 31, 32, MAX_BYTES] {
+            let (ct, hdk, block, _hek, _bid) = setup_hybrid_elgamal::<Fr, _, MAX_BYTES>(&mut rng, len);
+            assert_eq!(ct.body.len(), hybrid_body_len::<Fr>(MAX_BYTES));
+            assert_eq!(decrypt_hybrid_block(&hdk, &ct), Some(block), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn test_hybrid_circuit_variable_lengths() {
+        let mut rng = test_rng();
+        for len in [0, 5, 62, MAX_BYTES] {
+            let (ct, hdk, _block, hek, bid) = setup_hybrid_elgamal::<Fr, _, MAX_BYTES>(&mut rng, len);
+            let circuit = HybridElGamalEncryptionCircuit::<MAX_BYTES> { ct, hek, bid, hdk };
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            println!("Hybrid (max {} bytes, len {}) constraints: {}", MAX_BYTES, len, cs.num_constraints());
+            assert!(cs.is_satisfied().unwrap(), "Honest hybrid block of {} bytes should be satisfied", len);
+        }
+    }
+
+    #[test]
+    fn test_hybrid_circuit_rejects_tampering() {
+        let mut rng = test_rng();
+        let (ct, hdk, block, hek, bid) = setup_hybrid_elgamal::<Fr, _, MAX_BYTES>(&mut rng, 40);
+        let honest = HybridElGamalEncryptionCircuit::<MAX_BYTES> { ct: ct.clone(), hek, bid, hdk };
+
+        // A masked body element, the encapsulated key and the block ID
+        let mut body = ct.body.clone();
+        body[1] += Fr::from(1u64);
+        let mut kem = ct.kem;
+        kem[1] = Fr::rand(&mut rng);
+        let mut other_bid = bid;
+        other_bid[0] ^= 1;
+        let tampered = vec![
+            HybridElGamalEncryptionCircuit { ct: HybridCiphertext { body, ..ct.clone() }, ..honest.clone() },
+            HybridElGamalEncryptionCircuit { ct: HybridCiphertext { kem, ..ct.clone() }, ..honest.clone() },
+            HybridElGamalEncryptionCircuit { bid: other_bid, ..honest.clone() },
+            HybridElGamalEncryptionCircuit { hdk: Fr::rand(&mut rng), ..honest.clone() },
+        ];
+        for circuit in tampered {
+            assert!(!is_satisfied(circuit), "Tampered hybrid block must be rejected");
+        }
+
+        // Claiming a shorter length leaves a non-zero byte past the end of the block
+        let shorter = HybridCiphertext { len: 39, ..ct.clone() };
+        let shorter_bid = compute_hybrid_block_id::<Fr, MAX_BYTES>(&block[..39]);
+        assert!(!is_satisfied(HybridElGamalEncryptionCircuit { ct: shorter, bid: shorter_bid, ..honest }));
+    }
+
+    #[test]
+    fn test_undecryptable_kem_is_a_synthesis_error() {
+        let mut rng = test_rng();
+        let (ct, hdk, _block, hek, bid) = setup_hybrid_elgamal::<Fr, _, MAX_BYTES>(&mut rng, 40);
+
+        // c1 = 0 makes c1^hdk zero, so the KEM has no inverse to decrypt with
+        let ct = HybridCiphertext { kem: [Fr::from(0u64), ct.kem[1]], ..ct };
+        assert_eq!(decrypt_hybrid_block(&hdk, &ct), None);
+
+        let circuit = HybridElGamalEncryptionCircuit::<MAX_BYTES> { ct, hek, bid, hdk };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        assert!(matches!(circuit.generate_constraints(cs), Err(SynthesisError::AssignmentMissing)));
+    }
+
+    #[test]
+    fn test_hybrid_block_id_binds_length() {
+        let mut rng = test_rng();
+        let (_, _, _, hek, _) = setup_hybrid_elgamal::<Fr, _, MAX_BYTES>(&mut rng, 0);
+
+        // Trailing zero bytes pack to the same elements, but the length tells them apart
+        let block = [7u8, 0, 0];
+        assert_ne!(
+            compute_hybrid_block_id::<Fr, MAX_BYTES>(&block),
+            compute_hybrid_block_id::<Fr, MAX_BYTES>(&block[..1])
+        );
+        let ct = encrypt_hybrid_block::<Fr, _, MAX_BYTES>(&hek, &block, &mut rng);
+        assert_eq!(ct.len, 3);
+    }
+}
@@ -0,0 +1,112 @@
+pub mod aggregation;
+pub mod batch_verify;
+pub mod circuit;
+pub mod dispute;
+pub mod dispute_circuit;
+pub mod ec_circuit;
+pub mod elgamal;
+pub mod encoding;
+pub mod hd_circuit;
+pub mod hd_key;
+pub mod hybrid_circuit;
+pub mod pipeline;
+pub mod record_circuit;
+pub mod reencryption_circuit;
+pub mod signed_elgamal;
+pub mod solidity;
+pub mod threshold;
+
+#[cfg(test)]
+mod tests {
+    use crate::circuit::{ElGamalCorrectEncryptionCircuit, OptimizedElGamalEncryptionCircuit, OriginalElGamalEncryptionCircuit};
+    use crate::dispute::setup_corrupted_block;
+    use crate::dispute_circuit::BlockDisputeCircuit;
+    use crate::ec_circuit::JubjubElGamalEncryptionCircuit;
+    use crate::elgamal::{
+        compute_record_id, setup_ec_elgamal, setup_elgamal, setup_encryption_proof, setup_hybrid_elgamal, setup_record_elgamal,
+        setup_reencryption_proof,
+    };
+    use crate::hd_circuit::DerivedKeyElGamalCircuit;
+    use crate::hd_key::{setup_derived_elgamal, KeyPath};
+    use crate::hybrid_circuit::HybridElGamalEncryptionCircuit;
+    use crate::record_circuit::{RecordElGamalEncryptionCircuit, RecordPublicInputs};
+    use crate::reencryption_circuit::ElGamalReEncryptionCircuit;
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+    use circuit_snapshot::{check_snapshot, CircuitCounts};
+    use hash_gadgets::BlockIdHash;
+
+    /// Counts for every circuit in this crate, checked against `constraint-counts.snapshot`
+    #[test]
+    fn test_constraint_count_snapshot() {
+        let mut rng = test_rng();
+        let mut counts = Vec::new();
+
+        let (c1, c2, hdk, message, hek, bid) = setup_elgamal(&mut rng);
+        counts.push(CircuitCounts::of::<Fr, _>("original", OriginalElGamalEncryptionCircuit { ct: [c1, c2], bid, hdk }));
+        for (name, hash) in [
+            ("optimized_sha256", BlockIdHash::Sha256),
+            ("optimized_keccak256", BlockIdHash::Keccak256),
+            ("optimized_poseidon", BlockIdHash::Poseidon),
+        ] {
+            let circuit = OptimizedElGamalEncryptionCircuit { ct: [c1, c2], hek, bid: hash.block_id(&message), hdk, hash };
+            counts.push(CircuitCounts::of::<Fr, _>(name, circuit));
+        }
+
+        let (c1, c2, hdk, _message, hek, bid) = setup_elgamal::<ark_bn254::Fr, _>(&mut rng);
+        let circuit = OptimizedElGamalEncryptionCircuit { ct: [c1, c2], hek, bid, hdk, hash: BlockIdHash::Sha256 };
+        counts.push(CircuitCounts::of::<ark_bn254::Fr, _>("optimized_sha256_bn254", circuit));
+
+        let (c1, c2, y, message, hek, bid) = setup_encryption_proof(&mut rng);
+        let circuit = ElGamalCorrectEncryptionCircuit { ct: [c1, c2], hek, bid, y, message, hash: BlockIdHash::Sha256 };
+        counts.push(CircuitCounts::of::<Fr, _>("correct_encryption_sha256", circuit));
+
+        let (ct, hdk, hek, buyer_ct, y, buyer_pk, _message, bid) = setup_reencryption_proof(&mut rng);
+        let circuit = ElGamalReEncryptionCircuit { ct, hek, buyer_ct, buyer_pk, bid, hdk, y, hash: BlockIdHash::Sha256 };
+        counts.push(CircuitCounts::of::<Fr, _>("reencryption_sha256", circuit));
+
+        let path = KeyPath::BlockIndex(0);
+        let (c1, c2, master, master_commitment, _hdk, _message, hek, bid) = setup_derived_elgamal(&mut rng, &path);
+        let circuit = DerivedKeyElGamalCircuit {
+            ct: [c1, c2],
+            hek,
+            bid,
+            master_commitment,
+            path: path.to_field(),
+            master,
+            hash: BlockIdHash::Sha256,
+        };
+        counts.push(CircuitCounts::of::<Fr, _>("derived_key_sha256", circuit));
+
+        let (ct, hdk, bid) = setup_corrupted_block(&mut rng, BlockIdHash::Sha256);
+        let circuit = BlockDisputeCircuit { ct, hdk, bid, hash: BlockIdHash::Sha256 };
+        counts.push(CircuitCounts::of::<Fr, _>("dispute_sha256", circuit));
+
+        let (c1, c2, hdk, _message, hek, bid) = setup_ec_elgamal(&mut rng);
+        let circuit = JubjubElGamalEncryptionCircuit { ct: (c1, c2), hek, bid, hdk, hash: BlockIdHash::Sha256 };
+        counts.push(CircuitCounts::of::<Fr, _>("jubjub_sha256", circuit));
+
+        let (ct, hdk, _block, hek, bid) = setup_hybrid_elgamal::<Fr, _, 1024>(&mut rng, 700);
+        let circuit = HybridElGamalEncryptionCircuit::<1024> { ct, hek, bid, hdk };
+        counts.push(CircuitCounts::of::<Fr, _>("hybrid_1024", circuit));
+
+        let (cts, hdk, _messages, hek, bids) = setup_record_elgamal::<Fr, _, 2>(&mut rng);
+        for (name, public_inputs) in [
+            ("record2_per_block", RecordPublicInputs::PerBlock),
+            ("record2_record_id", RecordPublicInputs::RecordId),
+        ] {
+            let circuit = RecordElGamalEncryptionCircuit::<2> {
+                cts,
+                hek,
+                bids,
+                record_id: compute_record_id(&bids),
+                hdk,
+                hash: BlockIdHash::Sha256,
+                public_inputs,
+            };
+            counts.push(CircuitCounts::of::<Fr, _>(name, circuit));
+        }
+
+        check_snapshot(concat!(env!("CARGO_MANIFEST_DIR"), "/constraint-counts.snapshot"), &counts);
+    }
+}
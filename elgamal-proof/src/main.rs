@@ -1,197 +1,859 @@
-use ark_ff::{PrimeField, UniformRand, BigInteger};
-use ark_r1cs_std::prelude::*;
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
-use ark_bls12_381::Fr;
-use ark_groth16::Groth16;
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_groth16::{prepare_verifying_key, Groth16, Proof, VerifyingKey};
 use ark_crypto_primitives::snark::SNARK;
 use ark_std::rand::{Rng, SeedableRng};
 use ark_std::rand::rngs::StdRng;
-use ark_serialize::CanonicalSerialize;
-use std::time::Instant;
-use sha2::Digest;
-use circuit::OptimizedElGamalEncryptionCircuit;
+use ark_std::rand::thread_rng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use bench_report::{BenchmarkOptions, BenchmarkReport, Iteration, TimingStats};
+use std::time::{Duration, Instant};
+use elgamal_proof::aggregation::{aggregate_proofs, setup_simulated_srs, verify_aggregate_proof};
+use elgamal_proof::batch_verify::batch_verify;
+use elgamal_proof::circuit::{ElGamalCorrectEncryptionCircuit, OptimizedElGamalEncryptionCircuit};
+use elgamal_proof::dispute::setup_corrupted_block;
+use elgamal_proof::dispute_circuit::BlockDisputeCircuit;
+use elgamal_proof::ec_circuit::JubjubElGamalEncryptionCircuit;
+use elgamal_proof::elgamal::{
+    compute_record_id, setup_ec_elgamal, setup_elgamal, setup_encryption_proof, setup_hybrid_elgamal, setup_record_elgamal,
+    setup_reencryption_proof, HybridCiphertext,
+};
+use elgamal_proof::hd_circuit::DerivedKeyElGamalCircuit;
+use elgamal_proof::hd_key::{setup_derived_elgamal, KeyPath};
+use elgamal_proof::hybrid_circuit::HybridElGamalEncryptionCircuit;
+use elgamal_proof::pipeline::prove_blocks;
+use elgamal_proof::record_circuit::{RecordElGamalEncryptionCircuit, RecordPublicInputs};
+use elgamal_proof::reencryption_circuit::ElGamalReEncryptionCircuit;
+use elgamal_proof::solidity::{encode_calldata, render_verifier};
 use ark_bls12_381::Bls12_381;
-use ark_ff::Field;
+use ark_bn254::Bn254;
 use ark_relations::r1cs::ConstraintSystem;
+use hash_gadgets::BlockIdHash;
+use key_store::{
+    key_dir, load_proof, load_proving_key, load_verifying_key, load_witness, save_keys, save_proof, save_witness,
+    PROOF_FILE, PROVING_KEY_FILE, PUBLIC_INPUTS_FILE, VERIFYING_KEY_FILE, WITNESS_FILE,
+};
+use std::path::Path;
 
-mod circuit;
-
-/// Helper function to compute base^exponent in the field
-/// This is a simplified implementation - in practice you'd want more efficient exponentiation
-fn compute_power(base: &Fr, exponent: &Fr) -> Fr {
-    // Convert exponent to bits and use square-and-multiply
-    let exp_bits = exponent.into_bigint().to_bits_le();
-    let mut result = Fr::from(1u64);
-    let mut current_base = *base;
-    
-    for bit in exp_bits {
-        if bit {
-            result *= current_base;
-        }
-        current_base = current_base.square();
-    }
-    
-    result
-}
-
-/// Generates ElGamal parameters and encrypts a message
-fn setup_elgamal(rng: &mut StdRng) -> (Fr, Fr, Fr, Fr, Fr, [u8; 32]) {
-    let h2 = Fr::from(2u64); // Generator
-    let hdk = Fr::rand(rng); // Private key
-    let message = Fr::rand(rng); // Random message to encrypt
-    
-    // Compute public key: hek = h2^hdk mod p1
-    let hek = compute_power(&h2, &hdk);
-    
-    // ElGamal encryption
-    let y = Fr::rand(rng); // Random nonce
-    let c1 = compute_power(&h2, &y); // c1 = h2^y mod p1
-    let s = compute_power(&hek, &y); // s = hek^y mod p1 (shared secret)
-    let c2 = message * s; // c2 = m * s mod p1
-    
-    // Verify decryption works correctly
-    let decrypted_s = compute_power(&c1, &hdk); // s = c1^hdk mod p1
-    let decrypted_message = c2 * decrypted_s.inverse().unwrap(); // m = c2 * s^(-1) mod p1
-    assert_eq!(message, decrypted_message, "Decryption failed - implementation error!");
-    
-    // Compute block ID as SHA256 hash of the original message
-    let mut hasher = sha2::Sha256::new();
-    let message_bytes = message.into_bigint().to_bytes_le();
-    hasher.update(&message_bytes);
-    let hash_result = hasher.finalize();
-    let mut bid = [0u8; 32];
-    bid.copy_from_slice(&hash_result);
-    
-    (c1, c2, hdk, message, hek, bid)
-}
+/// Maximum block length of the `hybrid` variant; every proof pads its body to this size
+const HYBRID_MAX_BYTES: usize = 1024;
 
 /// Extracts public inputs from a circuit
-fn extract_public_inputs(circuit: &OptimizedElGamalEncryptionCircuit) -> Result<Vec<Fr>, SynthesisError> {
-    let cs = ConstraintSystem::<Fr>::new_ref();
+fn extract_public_inputs<F: PrimeField, C: ConstraintSynthesizer<F> + Clone>(circuit: &C) -> Result<Vec<F>, SynthesisError> {
+    let cs = ConstraintSystem::<F>::new_ref();
     circuit.clone().generate_constraints(cs.clone())?;
     
     let instance_assignment = cs.borrow().unwrap().instance_assignment.clone();
-    let public_inputs: Vec<Fr> = instance_assignment.iter().skip(1).cloned().collect();
+    let public_inputs: Vec<F> = instance_assignment.iter().skip(1).cloned().collect();
     
     Ok(public_inputs)
 }
 
+/// Prints the change from the SHA-256 baseline as an absolute and relative delta
+fn print_hash_deltas(hash: BlockIdHash, report: &BenchmarkReport, baseline: &BenchmarkReport) {
+    let percent = |new: f64, old: f64| (new - old) / old * 100.0;
+
+    println!("\n⚖️  === {:?} vs Sha256 block IDs ===", hash);
+    println!("   - Constraints: {} vs {} ({:+} / {:+.1}%)",
+             report.constraints, baseline.constraints,
+             report.constraints as i64 - baseline.constraints as i64,
+             percent(report.constraints as f64, baseline.constraints as f64));
+    println!("   - Avg setup: {:.2} ms vs {:.2} ms ({:+.1} ms / {:+.1}%)",
+             report.setup.mean_ms, baseline.setup.mean_ms,
+             report.setup.mean_ms - baseline.setup.mean_ms, percent(report.setup.mean_ms, baseline.setup.mean_ms));
+    println!("   - Avg prove: {:.2} ms vs {:.2} ms ({:+.1} ms / {:+.1}%)",
+             report.prove.mean_ms, baseline.prove.mean_ms,
+             report.prove.mean_ms - baseline.prove.mean_ms, percent(report.prove.mean_ms, baseline.prove.mean_ms));
+}
+
 fn main() {
     println!("=== ElGamal Decryption Proof System - Benchmark Analysis ===\n");
-    
-    const ITERATIONS: usize = 10;
-    let mut setup_times = Vec::new();
-    let mut prove_times = Vec::new();
-    let mut verify_times = Vec::new();
-    
-    let mut constraint_counts = Vec::new();
-    let mut variable_counts = Vec::new();
-    let mut proof_sizes = Vec::new();
-    
-    for i in 0..ITERATIONS {
-        println!("🔄 Running iteration {} of {}", i + 1, ITERATIONS);
-        
-        let mut rng = StdRng::seed_from_u64(12345 + i as u64); // Different seed each time
-        let (c1, c2, hdk, _message, _hek, bid) = setup_elgamal(&mut rng);
-        
-        let circuit = OptimizedElGamalEncryptionCircuit {
-            ct: [c1, c2],
-            bid,
-            hdk,
+
+    // An optional leading `setup`, `witness`, `prove` or `verify` works on the key files instead of benchmarking
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // `--iterations <n>`, `--seed <seed>` and `--out <dir>` may appear anywhere and configure the benchmark report
+    let options = BenchmarkOptions::from_args(&mut args);
+
+    let command = match args.first().map(String::as_str) {
+        Some("setup" | "witness" | "prove" | "verify") => Some(args.remove(0)),
+        _ => None,
+    };
+
+    // Select the proof with the first argument: `fr` (default), `jubjub`, `encryption`, `reencryption`,
+    // `derived`, `dispute`, `hybrid`, `record`, `record-id`, `batch`, `aggregate` or `pipeline`, or `solidity` to export an on-chain verifier for the proof of correct encryption
+    let variant = args.first().cloned().unwrap_or_else(|| "fr".to_string());
+
+    // Select the block ID hash with the second argument: `sha256` (default), `keccak` or `poseidon`
+    let hash = match args.get(1).map(String::as_str) {
+        None | Some("sha256") => BlockIdHash::Sha256,
+        Some("keccak") => BlockIdHash::Keccak256,
+        Some("poseidon") => BlockIdHash::Poseidon,
+        Some(other) => panic!("Unknown block ID hash `{}` (expected `sha256`, `keccak` or `poseidon`)", other),
+    };
+    println!("#️⃣  Block ID hash: {:?}", hash);
+
+    if variant == "solidity" {
+        return export_solidity_verifier(hash);
+    }
+
+    // Select the proving curve with the third argument: `bls12-381` (default) or `bn254`
+    let curve = args.get(2).cloned().unwrap_or_else(|| "bls12-381".to_string());
+    println!("📐 Proving curve: {}", curve);
+
+    assert!(variant != "jubjub" || curve != "bn254", "Jubjub is embedded in BLS12-381 and cannot be proven over BN254");
+
+    if let Some(command) = command {
+        let dir = key_dir(&format!("elgamal-{}-{:?}-{}", variant, hash, curve).to_lowercase());
+        return match curve.as_str() {
+            "bls12-381" => run_key_command::<Bls12_381>(&command, &variant, hash, &dir),
+            "bn254" => run_key_command::<Bn254>(&command, &variant, hash, &dir),
+            other => panic!("Unknown proving curve `{}` (expected `bls12-381` or `bn254`)", other),
         };
+    }
+
+    match curve.as_str() {
+        "bls12-381" => run::<Bls12_381>(&variant, hash, &curve, &options),
+        // BN254 has EVM precompiles, so these proofs can be verified on-chain
+        "bn254" => run::<Bn254>(&variant, hash, &curve, &options),
+        other => panic!("Unknown proving curve `{}` (expected `bls12-381` or `bn254`)", other),
+    }
+}
+
+/// Single-block decryption circuit for a fresh random key and message
+fn fr_circuit<F: PrimeField + Absorb>(rng: &mut StdRng, hash: BlockIdHash) -> OptimizedElGamalEncryptionCircuit<F> {
+    let (c1, c2, hdk, message, hek, _bid) = setup_elgamal(rng);
+    OptimizedElGamalEncryptionCircuit {
+        ct: [c1, c2],
+        hek,
+        bid: hash.block_id(&message),
+        hdk,
+        hash,
+    }
+}
+
+/// Jubjub EC-ElGamal decryption circuit for a fresh random key and message
+fn jubjub_circuit(rng: &mut StdRng, hash: BlockIdHash) -> JubjubElGamalEncryptionCircuit {
+    let (c1, c2, hdk, message, hek, _bid) = setup_ec_elgamal(rng);
+    JubjubElGamalEncryptionCircuit {
+        ct: (c1, c2),
+        hek,
+        bid: hash.block_id(&message),
+        hdk,
+        hash,
+    }
+}
+
+/// Seller's proof of correct encryption for a fresh random key, message and nonce
+fn encryption_circuit<F: PrimeField + Absorb>(rng: &mut StdRng, hash: BlockIdHash) -> ElGamalCorrectEncryptionCircuit<F> {
+    let (c1, c2, y, message, hek, _bid) = setup_encryption_proof(rng);
+    ElGamalCorrectEncryptionCircuit {
+        ct: [c1, c2],
+        hek,
+        bid: hash.block_id(&message),
+        y,
+        message,
+        hash,
+    }
+}
+
+/// Seller's proof of re-encryption to a fresh buyer key
+fn reencryption_circuit<F: PrimeField + Absorb>(rng: &mut StdRng, hash: BlockIdHash) -> ElGamalReEncryptionCircuit<F> {
+    let (ct, hdk, hek, buyer_ct, y, buyer_pk, message, _bid) = setup_reencryption_proof(rng);
+    ElGamalReEncryptionCircuit {
+        ct,
+        hek,
+        buyer_ct,
+        buyer_pk,
+        bid: hash.block_id(&message),
+        hdk,
+        y,
+        hash,
+    }
+}
+
+/// Decryption circuit for a block key derived from a fresh master key
+fn derived_circuit<F: PrimeField + Absorb>(rng: &mut StdRng, hash: BlockIdHash) -> DerivedKeyElGamalCircuit<F> {
+    let path = KeyPath::BlockIndex(rng.gen_range(0..1000));
+    let (c1, c2, master, master_commitment, _hdk, message, hek, _bid) = setup_derived_elgamal(rng, &path);
+    DerivedKeyElGamalCircuit {
+        ct: [c1, c2],
+        hek,
+        bid: hash.block_id(&message),
+        master_commitment,
+        path: path.to_field(),
+        master,
+        hash,
+    }
+}
+
+/// Buyer's dispute of a block whose ciphertext was corrupted after its block ID was committed
+fn dispute_circuit<F: PrimeField + Absorb>(rng: &mut StdRng, hash: BlockIdHash) -> BlockDisputeCircuit<F> {
+    let (ct, hdk, bid) = setup_corrupted_block(rng, hash);
+    BlockDisputeCircuit { ct, hdk, bid, hash }
+}
+
+/// Hybrid KEM/DEM decryption circuit for a fresh random key and a block of random length
+fn hybrid_circuit<F: PrimeField + Absorb>(rng: &mut StdRng) -> HybridElGamalEncryptionCircuit<HYBRID_MAX_BYTES, F> {
+    let len = rng.gen_range(0..=HYBRID_MAX_BYTES);
+    let (ct, hdk, _block, hek, bid) = setup_hybrid_elgamal::<F, _, HYBRID_MAX_BYTES>(rng, len);
+    HybridElGamalEncryptionCircuit { ct, hek, bid, hdk }
+}
+
+/// Runs `setup`, `witness`, `prove` or `verify` for a single-block variant against the key files in `dir`
+///
+/// Each variant reads and writes its block as the tuple documented next to the key layout in
+/// `key_store`; the random circuits of the benchmarks provide the `setup` shape and the
+/// example witness.
+fn run_key_command<E: Pairing>(command: &str, variant: &str, hash: BlockIdHash, dir: &Path)
+where
+    E::ScalarField: Absorb,
+{
+    match variant {
+        "fr" => key_file_command::<E, _, _>(
+            command,
+            dir,
+            |rng| {
+                let c = fr_circuit(rng, hash);
+                (c.ct, c.hek, c.bid, c.hdk)
+            },
+            |(ct, hek, bid, hdk)| OptimizedElGamalEncryptionCircuit { ct, hek, bid, hdk, hash },
+        ),
+        "jubjub" => key_file_command::<Bls12_381, _, _>(
+            command,
+            dir,
+            |rng| {
+                let c = jubjub_circuit(rng, hash);
+                (c.ct, c.hek, c.bid, c.hdk)
+            },
+            |(ct, hek, bid, hdk)| JubjubElGamalEncryptionCircuit { ct, hek, bid, hdk, hash },
+        ),
+        "encryption" => key_file_command::<E, _, _>(
+            command,
+            dir,
+            |rng| {
+                let c = encryption_circuit(rng, hash);
+                (c.ct, c.hek, c.bid, c.y, c.message)
+            },
+            |(ct, hek, bid, y, message)| ElGamalCorrectEncryptionCircuit { ct, hek, bid, y, message, hash },
+        ),
+        "reencryption" => key_file_command::<E, _, _>(
+            command,
+            dir,
+            |rng| {
+                let c = reencryption_circuit(rng, hash);
+                ((c.ct, c.hek), (c.buyer_ct, c.buyer_pk), c.bid, (c.hdk, c.y))
+            },
+            |((ct, hek), (buyer_ct, buyer_pk), bid, (hdk, y))| ElGamalReEncryptionCircuit {
+                ct,
+                hek,
+                buyer_ct,
+                buyer_pk,
+                bid,
+                hdk,
+                y,
+                hash,
+            },
+        ),
+        "derived" => key_file_command::<E, _, _>(
+            command,
+            dir,
+            |rng| {
+                let c = derived_circuit(rng, hash);
+                ((c.ct, c.hek), c.bid, (c.master_commitment, c.path), c.master)
+            },
+            |((ct, hek), bid, (master_commitment, path), master)| DerivedKeyElGamalCircuit {
+                ct,
+                hek,
+                bid,
+                master_commitment,
+                path,
+                master,
+                hash,
+            },
+        ),
+        "dispute" => key_file_command::<E, _, _>(
+            command,
+            dir,
+            |rng| {
+                let c = dispute_circuit(rng, hash);
+                (c.ct, c.hdk, c.bid)
+            },
+            |(ct, hdk, bid)| BlockDisputeCircuit { ct, hdk, bid, hash },
+        ),
+        "hybrid" => key_file_command::<E, _, _>(
+            command,
+            dir,
+            |rng| {
+                let c = hybrid_circuit(rng);
+                ((c.ct.kem, c.ct.body, c.ct.len), c.hek, c.bid, c.hdk)
+            },
+            |((kem, body, len), hek, bid, hdk)| HybridElGamalEncryptionCircuit::<HYBRID_MAX_BYTES, _> {
+                ct: HybridCiphertext { kem, body, len },
+                hek,
+                bid,
+                hdk,
+            },
+        ),
+        other => panic!(
+            "Key files are supported for `fr`, `jubjub`, `encryption`, `reencryption`, `derived`, `dispute` and `hybrid`, not `{}`",
+            other
+        ),
+    }
+}
+
+/// `setup` writes fresh keys, `witness` writes a random block to prove, `prove` proves the
+/// block in the witness file with the stored proving key and `verify` checks the stored proof
+/// with the stored verifying key
+fn key_file_command<E, W, C>(
+    command: &str,
+    dir: &Path,
+    random_witness: impl Fn(&mut StdRng) -> W,
+    make_circuit: impl Fn(W) -> C,
+) where
+    E: Pairing,
+    W: CanonicalSerialize + CanonicalDeserialize,
+    C: ConstraintSynthesizer<E::ScalarField> + Clone,
+{
+    let mut rng = StdRng::from_rng(thread_rng()).expect("Failed to seed the RNG");
+    match command {
+        "setup" => {
+            let setup_start = Instant::now();
+            let (pk, vk) = Groth16::<E>::circuit_specific_setup(make_circuit(random_witness(&mut rng)), &mut rng)
+                .expect("Failed to perform trusted setup");
+            println!("🔧 Setup: {:?}", setup_start.elapsed());
+            save_keys(dir, &pk, &vk).expect("Failed to write keys");
+            println!("🔑 Wrote {} and {} to {}", PROVING_KEY_FILE, VERIFYING_KEY_FILE, dir.display());
+        }
+        "witness" => {
+            save_witness(dir, &random_witness(&mut rng)).expect("Failed to write witness");
+            println!("🧾 Wrote a random block to {} in {}", WITNESS_FILE, dir.display());
+        }
+        "prove" => {
+            let pk = load_proving_key::<E>(dir).expect("Failed to read proving key; run `setup` first");
+            let witness = load_witness::<W>(dir).unwrap_or_else(|err| {
+                panic!("Failed to read {} in {} ({}); write the block to prove there first", WITNESS_FILE, dir.display(), err)
+            });
+            let circuit = make_circuit(witness);
+
+            // Groth16 would still output a proof for a wrong witness, it just would not verify
+            let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+            circuit.clone().generate_constraints(cs.clone()).expect("Failed to synthesize the circuit");
+            if !cs.is_satisfied().unwrap() {
+                println!("❌ The block in {} does not satisfy the circuit", WITNESS_FILE);
+                std::process::exit(1);
+            }
+
+            let prove_start = Instant::now();
+            let proof = Groth16::<E>::prove(&pk, circuit.clone(), &mut rng)
+                .expect("Failed to generate proof");
+            println!("🎯 Prove: {:?}", prove_start.elapsed());
+            let public_inputs = extract_public_inputs(&circuit).unwrap();
+            save_proof(dir, &proof, &public_inputs).expect("Failed to write proof");
+            println!("📄 Wrote {} and {} to {}", PROOF_FILE, PUBLIC_INPUTS_FILE, dir.display());
+        }
+        "verify" => {
+            let vk = load_verifying_key::<E>(dir).expect("Failed to read verifying key; run `setup` first");
+            let (proof, public_inputs) = load_proof::<E>(dir).expect("Failed to read proof; run `prove` first");
+            let verify_start = Instant::now();
+            let is_valid = Groth16::<E>::verify(&vk, &public_inputs, &proof)
+                .expect("Failed to verify proof");
+            println!("🔍 Verify: {:?}", verify_start.elapsed());
+            if !is_valid {
+                println!("❌ Proof in {} is invalid", dir.display());
+                std::process::exit(1);
+            }
+            println!("✅ Proof in {} is valid", dir.display());
+        }
+        other => unreachable!("unknown key command `{}`", other),
+    }
+}
+
+/// Runs the selected variant with Groth16 over the pairing engine `E`
+fn run<E: Pairing>(variant: &str, hash: BlockIdHash, curve: &str, options: &BenchmarkOptions)
+where
+    E::ScalarField: Absorb,
+{
+    match variant {
+        "record" => return run_record_scaling::<E>(variant, RecordPublicInputs::PerBlock, hash, curve, options),
+        "record-id" => return run_record_scaling::<E>(variant, RecordPublicInputs::RecordId, hash, curve, options),
+        "batch" => return run_batch_verification::<E>(hash, curve, options),
+        "aggregate" => return run_aggregation::<E>(hash, curve, options),
+        "pipeline" => return run_pipeline::<E>(hash, curve, options),
+        // Hybrid block IDs are always Poseidon, so there is no hash to compare against
+        "hybrid" => {
+            let label = format!("Hybrid KEM/DEM (blocks up to {} bytes)", HYBRID_MAX_BYTES);
+            run_benchmark::<E, _, _>(&label, &format!("hybrid-{}", curve), options, hybrid_circuit);
+            return;
+        }
+        _ => {}
+    }
+
+    let report = run_variant::<E>(variant, hash, curve, options);
+
+    // Other hashes are always reported against the SHA-256 baseline
+    if hash != BlockIdHash::Sha256 {
+        println!("\n🔁 Re-running with Sha256 block IDs for comparison\n");
+        let baseline = run_variant::<E>(variant, BlockIdHash::Sha256, curve, options);
+        print_hash_deltas(hash, &report, &baseline);
+    }
+}
+
+/// Writes a BN254 verifier contract for the proof of correct encryption and prints sample calldata
+fn export_solidity_verifier(hash: BlockIdHash) {
+    let mut rng = StdRng::seed_from_u64(12345);
+    let (c1, c2, y, message, hek, _bid) = setup_encryption_proof(&mut rng);
+    let circuit = ElGamalCorrectEncryptionCircuit { ct: [c1, c2], hek, bid: hash.block_id(&message), y, message, hash };
+
+    let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(circuit.clone(), &mut rng)
+        .expect("Failed to perform trusted setup");
+    let proof = Groth16::<Bn254>::prove(&pk, circuit.clone(), &mut rng)
+        .expect("Failed to generate proof");
+    let public_inputs = extract_public_inputs(&circuit).unwrap();
+
+    let path = "ElGamalEncryptionVerifier.sol";
+    std::fs::write(path, render_verifier(&vk, "ElGamalEncryptionVerifier")).expect("Failed to write verifier");
+    println!("📝 Wrote {} ({} public inputs)", path, public_inputs.len());
+    println!("📨 verifyProof calldata: 0x{}", hex::encode(encode_calldata(&proof, &public_inputs)));
+}
+
+/// Benchmarks a single-block variant with the given block ID hash
+/// The report is named `<variant>-<hash>-<curve>`, like the variant's key directory.
+fn run_variant<E: Pairing>(variant: &str, hash: BlockIdHash, curve: &str, options: &BenchmarkOptions) -> BenchmarkReport
+where
+    E::ScalarField: Absorb,
+{
+    let name = format!("{}-{:?}-{}", variant, hash, curve).to_lowercase();
+    match variant {
+        "fr" => run_benchmark::<E, _, _>("Fr multiplicative group", &name, options, |rng| fr_circuit(rng, hash)),
+        // Jubjub is embedded in BLS12-381, so this variant always runs on that curve
+        "jubjub" => run_benchmark::<Bls12_381, _, _>("Jubjub EC-ElGamal", &name, options, |rng| jubjub_circuit(rng, hash)),
+        "encryption" => run_benchmark::<E, _, _>("Proof of correct encryption (seller)", &name, options, |rng| encryption_circuit(rng, hash)),
+        "reencryption" => run_benchmark::<E, _, _>("Proof of re-encryption to the buyer (seller)", &name, options, |rng| reencryption_circuit(rng, hash)),
+        "derived" => run_benchmark::<E, _, _>("Key derived from a committed master key", &name, options, |rng| derived_circuit(rng, hash)),
+        "dispute" => run_benchmark::<E, _, _>("Fraud proof of a corrupted block (buyer)", &name, options, |rng| dispute_circuit(rng, hash)),
+        other => panic!("Unknown ElGamal variant `{}` (expected `fr`, `jubjub`, `encryption`, `reencryption`, `derived`, `dispute`, `hybrid`, `record`, `record-id`, `batch`, `aggregate`, `pipeline` or `solidity`)", other),
+    }
+}
+
+/// Proves whole records of N blocks under one key and reports how the cost scales with N
+/// Every N runs `options.iterations` times and is written as the report `<variant>-<N>-<hash>-<curve>`.
+fn run_record_scaling<E: Pairing>(
+    variant: &str,
+    public_inputs: RecordPublicInputs,
+    hash: BlockIdHash,
+    curve: &str,
+    options: &BenchmarkOptions,
+) where
+    E::ScalarField: Absorb,
+{
+    println!("📦 Record proofs: N ciphertext/bid pairs in a single Groth16 proof ({:?} public inputs)", public_inputs);
+    println!("   Mean over {} iterations\n", options.iterations);
+    println!("{:>4} | {:>12} | {:>12} | {:>12} | {:>12} | {:>14}",
+             "N", "Constraints", "Setup", "Prove", "Verify", "Prove / block");
+    if hash != BlockIdHash::Sha256 {
+        println!("       (each row is followed by its delta against Sha256 block IDs)");
+    }
+
+    record_scaling_row::<E, 1>(variant, public_inputs, hash, curve, options);
+    record_scaling_row::<E, 2>(variant, public_inputs, hash, curve, options);
+    record_scaling_row::<E, 4>(variant, public_inputs, hash, curve, options);
+    record_scaling_row::<E, 8>(variant, public_inputs, hash, curve, options);
+    record_scaling_row::<E, 16>(variant, public_inputs, hash, curve, options);
+    record_scaling_row::<E, 32>(variant, public_inputs, hash, curve, options);
+    record_scaling_row::<E, 64>(variant, public_inputs, hash, curve, options);
+    println!("\n📝 Wrote the reports to {}", options.out_dir.display());
+}
+
+/// Benchmarks a record of N blocks and prints its table row
+fn record_scaling_row<E: Pairing, const N: usize>(
+    variant: &str,
+    public_inputs: RecordPublicInputs,
+    hash: BlockIdHash,
+    curve: &str,
+    options: &BenchmarkOptions,
+) where
+    E::ScalarField: Absorb,
+{
+    let report = record_report::<E, N>(variant, public_inputs, hash, curve, options);
+    println!("{:>4} | {:>12} | {:>10.2}ms | {:>10.2}ms | {:>10.2}ms | {:>12.2}ms",
+             N, report.constraints, report.setup.mean_ms, report.prove.mean_ms, report.verify.mean_ms,
+             report.prove.mean_ms / N as f64);
+
+    if hash != BlockIdHash::Sha256 {
+        let baseline = record_report::<E, N>(variant, public_inputs, BlockIdHash::Sha256, curve, options);
+        println!("{:>4} | {:>+12} | {:>+10.1}ms | {:>+10.1}ms | {:>12} | {:>14}",
+                 "Δ", report.constraints as i64 - baseline.constraints as i64,
+                 report.setup.mean_ms - baseline.setup.mean_ms, report.prove.mean_ms - baseline.prove.mean_ms, "", "");
+    }
+}
+
+/// Proves a record of N blocks once per iteration and writes the report
+fn record_report<E: Pairing, const N: usize>(
+    variant: &str,
+    public_inputs: RecordPublicInputs,
+    hash: BlockIdHash,
+    curve: &str,
+    options: &BenchmarkOptions,
+) -> BenchmarkReport
+where
+    E::ScalarField: Absorb,
+{
+    let iterations: Vec<Iteration> = (0..options.iterations)
+        .map(|i| time_record_proof::<E, N>(public_inputs, hash, options.seed.wrapping_add(i as u64)))
+        .collect();
+    write_report(&format!("{}-{}-{:?}-{}", variant, N, hash, curve), options, &iterations)
+}
+
+/// Proves a record of N blocks once with the RNG seeded by `seed`
+fn time_record_proof<E: Pairing, const N: usize>(public_inputs: RecordPublicInputs, hash: BlockIdHash, seed: u64) -> Iteration
+where
+    E::ScalarField: Absorb,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (cts, hdk, messages, hek, _bids) = setup_record_elgamal::<E::ScalarField, _, N>(&mut rng);
+    let bids = messages.map(|message| hash.block_id(&message));
+    let record_id = compute_record_id(&bids);
+    let circuit = RecordElGamalEncryptionCircuit::<N, E::ScalarField> {
+        cts,
+        hek,
+        bids,
+        record_id,
+        hdk,
+        hash,
+        public_inputs,
+    };
+
+    let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+
+    let setup_start = Instant::now();
+    let (pk, vk) = Groth16::<E>::circuit_specific_setup(circuit.clone(), &mut rng)
+        .expect("Failed to perform trusted setup");
+    let setup_time = setup_start.elapsed();
+
+    let prove_start = Instant::now();
+    let proof = Groth16::<E>::prove(&pk, circuit.clone(), &mut rng)
+        .expect("Failed to generate proof");
+    let prove_time = prove_start.elapsed();
+
+    let public_inputs = extract_public_inputs(&circuit).unwrap();
+    let verify_start = Instant::now();
+    let is_valid = Groth16::<E>::verify(&vk, &public_inputs, &proof)
+        .expect("Failed to verify proof");
+    let verify_time = verify_start.elapsed();
+    assert!(is_valid, "Record proof should be valid");
+
+    Iteration {
+        constraints: cs.num_constraints(),
+        witnesses: cs.num_witness_variables(),
+        proof_bytes: proof.compressed_size(),
+        setup: setup_time,
+        prove: prove_time,
+        verify: verify_time,
+    }
+}
+
+/// Groth16 keys for the `fr` circuit and proofs of random blocks under them
+struct ProvenBlocks<E: Pairing> {
+    vk: VerifyingKey<E>,
+    proofs: Vec<Proof<E>>,
+    public_inputs: Vec<Vec<E::ScalarField>>,
+    /// Circuit size, setup time and the time to prove every block; the verify time is unset
+    iteration: Iteration,
+}
+
+/// Sets up the `fr` circuit and proves `count` random blocks under the one proving key
+fn prove_random_blocks<E: Pairing>(rng: &mut StdRng, hash: BlockIdHash, count: usize) -> ProvenBlocks<E>
+where
+    E::ScalarField: Absorb,
+{
+    let circuit = fr_circuit::<E::ScalarField>(rng, hash);
+    let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+
+    let setup_start = Instant::now();
+    let (pk, vk) = Groth16::<E>::circuit_specific_setup(circuit, rng)
+        .expect("Failed to perform trusted setup");
+    let setup_time = setup_start.elapsed();
+
+    let circuits: Vec<_> = (0..count).map(|_| fr_circuit::<E::ScalarField>(rng, hash)).collect();
+    let public_inputs: Vec<_> = circuits.iter().map(|circuit| extract_public_inputs(circuit).unwrap()).collect();
+    let prove_start = Instant::now();
+    let proofs: Vec<_> = circuits
+        .into_iter()
+        .map(|circuit| Groth16::<E>::prove(&pk, circuit, rng).expect("Failed to generate proof"))
+        .collect();
+    let prove_time = prove_start.elapsed();
+
+    let iteration = Iteration {
+        constraints: cs.num_constraints(),
+        witnesses: cs.num_witness_variables(),
+        proof_bytes: proofs[0].compressed_size(),
+        setup: setup_time,
+        prove: prove_time,
+        verify: Duration::ZERO,
+    };
+    ProvenBlocks { vk, proofs, public_inputs, iteration }
+}
+
+/// Verifies N single-block proofs under one key, one by one as in `run_benchmark` and as a batch
+///
+/// Only the first `DISTINCT_PROOFS` blocks are proven; larger batches repeat them, which does
+/// not change the cost of either verification path. Every N is written as the reports
+/// `batch-<N>-loop` and `batch-<N>-batch`, both with the setup and prove times of the shared proofs.
+fn run_batch_verification<E: Pairing>(hash: BlockIdHash, curve: &str, options: &BenchmarkOptions)
+where
+    E::ScalarField: Absorb,
+{
+    const DISTINCT_PROOFS: usize = 16;
+    const BATCH_SIZES: [usize; 4] = [1, 10, 100, 1000];
+    println!("📦 Batch verification: N proofs under one verifying key\n");
+
+    let mut loop_iterations = vec![Vec::new(); BATCH_SIZES.len()];
+    let mut batch_iterations = vec![Vec::new(); BATCH_SIZES.len()];
+    for i in 0..options.iterations {
+        println!("🔄 Running iteration {} of {}", i + 1, options.iterations);
+        let mut rng = StdRng::seed_from_u64(options.seed.wrapping_add(i as u64));
+        let blocks = prove_random_blocks::<E>(&mut rng, hash, DISTINCT_PROOFS);
+        let pvk = prepare_verifying_key(&blocks.vk);
+
+        for (j, n) in BATCH_SIZES.into_iter().enumerate() {
+            let batch_proofs: Vec<_> = blocks.proofs.iter().cycle().take(n).cloned().collect();
+            let batch_inputs: Vec<_> = blocks.public_inputs.iter().cycle().take(n).cloned().collect();
+            let proof_bytes = n * blocks.iteration.proof_bytes;
+
+            let loop_start = Instant::now();
+            for (proof, inputs) in batch_proofs.iter().zip(batch_inputs.iter()) {
+                let is_valid = Groth16::<E>::verify(&blocks.vk, inputs, proof).expect("Failed to verify proof");
+                assert!(is_valid, "Proof should be valid");
+            }
+            loop_iterations[j].push(Iteration { proof_bytes, verify: loop_start.elapsed(), ..blocks.iteration });
+
+            let batch_start = Instant::now();
+            batch_verify(&pvk, &batch_proofs, &batch_inputs, &mut rng).expect("Batch should be valid");
+            batch_iterations[j].push(Iteration { proof_bytes, verify: batch_start.elapsed(), ..blocks.iteration });
+        }
+    }
+
+    println!("\n{:>5} | {:>12} | {:>12} | {:>8}", "N", "Loop", "Batch", "Speedup");
+    for (j, n) in BATCH_SIZES.into_iter().enumerate() {
+        let looped = write_report(&format!("batch-{}-loop-{:?}-{}", n, hash, curve), options, &loop_iterations[j]);
+        let batched = write_report(&format!("batch-{}-batch-{:?}-{}", n, hash, curve), options, &batch_iterations[j]);
+        println!("{:>5} | {:>10.2}ms | {:>10.2}ms | {:>7.1}x",
+                 n, looped.verify.mean_ms, batched.verify.mean_ms, looped.verify.mean_ms / batched.verify.mean_ms);
+    }
+    println!("\n📝 Wrote the reports to {}", options.out_dir.display());
+}
+
+/// Aggregates N proofs into one SnarkPack proof and compares it with shipping the N proofs
+///
+/// Every N is written as the reports `aggregate-<N>-loop`, verifying the N proofs one by one,
+/// and `aggregate-<N>-aggregate`, whose prove time includes the aggregation.
+fn run_aggregation<E: Pairing>(hash: BlockIdHash, curve: &str, options: &BenchmarkOptions)
+where
+    E::ScalarField: Absorb,
+{
+    const DISTINCT_PROOFS: usize = 16;
+    const MAX_PROOFS: usize = 1024;
+    const AGGREGATE_SIZES: [usize; 4] = [1, 16, 128, 1024];
+    println!("🧺 Proof aggregation: N proofs under one verifying key\n");
+
+    let mut loop_iterations = vec![Vec::new(); AGGREGATE_SIZES.len()];
+    let mut aggregate_iterations = vec![Vec::new(); AGGREGATE_SIZES.len()];
+    let mut aggregate_times = vec![Vec::new(); AGGREGATE_SIZES.len()];
+    for i in 0..options.iterations {
+        println!("🔄 Running iteration {} of {}", i + 1, options.iterations);
+        let mut rng = StdRng::seed_from_u64(options.seed.wrapping_add(i as u64));
+        let blocks = prove_random_blocks::<E>(&mut rng, hash, DISTINCT_PROOFS);
+        let pvk = prepare_verifying_key(&blocks.vk);
+
+        let srs_start = Instant::now();
+        let srs = setup_simulated_srs::<E, _>(&mut rng, MAX_PROOFS);
+        let srs_vk = srs.verifier_key();
+        println!("   🔧 Simulated SRS for {} proofs: {:?}", srs.max_proofs(), srs_start.elapsed());
+
+        for (j, n) in AGGREGATE_SIZES.into_iter().enumerate() {
+            let batch_proofs: Vec<_> = blocks.proofs.iter().cycle().take(n).cloned().collect();
+            let batch_inputs: Vec<_> = blocks.public_inputs.iter().cycle().take(n).cloned().collect();
+
+            let aggregate_start = Instant::now();
+            let aggregate = aggregate_proofs(&srs, &batch_proofs, &batch_inputs).expect("Failed to aggregate proofs");
+            let aggregate_time = aggregate_start.elapsed();
+
+            let loop_start = Instant::now();
+            for (proof, inputs) in batch_proofs.iter().zip(batch_inputs.iter()) {
+                let is_valid = Groth16::<E>::verify(&blocks.vk, inputs, proof).expect("Failed to verify proof");
+                assert!(is_valid, "Proof should be valid");
+            }
+            loop_iterations[j].push(Iteration {
+                proof_bytes: n * blocks.iteration.proof_bytes,
+                verify: loop_start.elapsed(),
+                ..blocks.iteration
+            });
+
+            let verify_start = Instant::now();
+            verify_aggregate_proof(&srs_vk, &pvk, &batch_inputs, &aggregate).expect("Aggregate should be valid");
+            aggregate_iterations[j].push(Iteration {
+                proof_bytes: aggregate.compressed_size(),
+                prove: blocks.iteration.prove + aggregate_time,
+                verify: verify_start.elapsed(),
+                ..blocks.iteration
+            });
+            aggregate_times[j].push(aggregate_time);
+        }
+    }
+
+    println!("\n{:>5} | {:>12} | {:>13} | {:>12} | {:>12} | {:>12}",
+             "N", "Proofs (B)", "Aggregate (B)", "Aggregate", "Verify loop", "Verify agg");
+    for (j, n) in AGGREGATE_SIZES.into_iter().enumerate() {
+        let looped = write_report(&format!("aggregate-{}-loop-{:?}-{}", n, hash, curve), options, &loop_iterations[j]);
+        let aggregated = write_report(&format!("aggregate-{}-aggregate-{:?}-{}", n, hash, curve), options, &aggregate_iterations[j]);
+        let aggregate_time = TimingStats::from_samples(&aggregate_times[j]);
+        println!("{:>5} | {:>12} | {:>13} | {:>10.2}ms | {:>10.2}ms | {:>10.2}ms",
+                 n, looped.proof_bytes, aggregated.proof_bytes, aggregate_time.mean_ms,
+                 looped.verify.mean_ms, aggregated.verify.mean_ms);
+    }
+    println!("\n📝 Wrote the reports to {}", options.out_dir.display());
+}
+
+/// Proves many blocks under one proving key, one after another and then with the parallel pipeline
+///
+/// Written as the reports `pipeline-sequential` and `pipeline-parallel`; the prove time covers
+/// all blocks and the verify time is one batch verification of their proofs.
+fn run_pipeline<E: Pairing>(hash: BlockIdHash, curve: &str, options: &BenchmarkOptions)
+where
+    E::ScalarField: Absorb,
+{
+    const BLOCKS: usize = 64;
+    println!("🏭 Proving pipeline: {} blocks under one proving key on {} threads\n", BLOCKS, rayon::current_num_threads());
+
+    let mut sequential_iterations = Vec::new();
+    let mut pipeline_iterations = Vec::new();
+    for i in 0..options.iterations {
+        println!("🔄 Running iteration {} of {}", i + 1, options.iterations);
+        let mut rng = StdRng::seed_from_u64(options.seed.wrapping_add(i as u64));
+        let circuit = fr_circuit::<E::ScalarField>(&mut rng, hash);
+        let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+
+        let setup_start = Instant::now();
+        let (pk, vk) = Groth16::<E>::circuit_specific_setup(circuit, &mut rng)
+            .expect("Failed to perform trusted setup");
+        let setup_time = setup_start.elapsed();
+        let pvk = prepare_verifying_key(&vk);
+
+        let circuits: Vec<_> = (0..BLOCKS).map(|_| fr_circuit::<E::ScalarField>(&mut rng, hash)).collect();
+        let public_inputs: Vec<_> = circuits.iter().map(|circuit| extract_public_inputs(circuit).unwrap()).collect();
+
+        let sequential_start = Instant::now();
+        let sequential_proofs: Vec<_> = circuits
+            .iter()
+            .map(|circuit| Groth16::<E>::prove(&pk, circuit.clone(), &mut rng).expect("Failed to generate proof"))
+            .collect();
+        let sequential_time = sequential_start.elapsed();
+
+        let output = prove_blocks(&pk, circuits.iter().map(|circuit| (circuit.ct, circuit.bid, circuit.hdk)), hash, &mut rng);
+        let proofs: Vec<_> = output.proofs.into_iter().map(|proof| proof.expect("Every block should be provable")).collect();
+
+        let verify_start = Instant::now();
+        batch_verify(&pvk, &sequential_proofs, &public_inputs, &mut rng).expect("Sequential proofs should be valid");
+        let sequential_verify = verify_start.elapsed();
+        let verify_start = Instant::now();
+        batch_verify(&pvk, &proofs, &public_inputs, &mut rng).expect("Pipeline proofs should be valid");
+        let pipeline_verify = verify_start.elapsed();
+
+        let iteration = Iteration {
+            constraints: cs.num_constraints(),
+            witnesses: cs.num_witness_variables(),
+            proof_bytes: BLOCKS * proofs[0].compressed_size(),
+            setup: setup_time,
+            prove: sequential_time,
+            verify: sequential_verify,
+        };
+        sequential_iterations.push(iteration);
+        pipeline_iterations.push(Iteration { prove: output.elapsed, verify: pipeline_verify, ..iteration });
+    }
+
+    let sequential = write_report(&format!("pipeline-sequential-{:?}-{}", hash, curve), options, &sequential_iterations);
+    let pipeline = write_report(&format!("pipeline-parallel-{:?}-{}", hash, curve), options, &pipeline_iterations);
+    let rate = |prove_ms: f64| BLOCKS as f64 / prove_ms * 1000.0;
+    println!("\n   - Sequential: {:>10.2}ms ({:.1} blocks/s)", sequential.prove.mean_ms, rate(sequential.prove.mean_ms));
+    println!("   - Pipeline:   {:>10.2}ms ({:.1} blocks/s)", pipeline.prove.mean_ms, rate(pipeline.prove.mean_ms));
+    println!("   - Speedup:    {:.1}x", sequential.prove.mean_ms / pipeline.prove.mean_ms);
+    println!("\n📝 Wrote the reports to {}", options.out_dir.display());
+}
+
+/// Summarises the iterations of one benchmark as the report `elgamal-proof-<name>` and writes it
+fn write_report(name: &str, options: &BenchmarkOptions, iterations: &[Iteration]) -> BenchmarkReport {
+    let report = BenchmarkReport::new("elgamal-proof", &name.to_lowercase(), options, iterations);
+    report.write(&options.out_dir).expect("Failed to write benchmark report");
+    report
+}
+
+/// Runs setup, proving and verification for the circuit built by `make_circuit`, prints statistics
+/// and writes them to the report directory as `elgamal-proof-<name>.csv` and `.json`
+fn run_benchmark<E, C, G>(label: &str, name: &str, options: &BenchmarkOptions, make_circuit: G) -> BenchmarkReport
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField> + Clone,
+    G: Fn(&mut StdRng) -> C,
+{
+    println!("🔐 Variant: {}\n", label);
+
+    let mut iterations = Vec::new();
+    for i in 0..options.iterations {
+        println!("🔄 Running iteration {} of {}", i + 1, options.iterations);
+        
+        let mut rng = StdRng::seed_from_u64(options.seed.wrapping_add(i as u64)); // Different seed each time
+        let circuit = make_circuit(&mut rng);
         
         // Measure constraint generation (for stats)
-        let cs = ConstraintSystem::<Fr>::new_ref();
+        let cs = ConstraintSystem::<E::ScalarField>::new_ref();
         circuit.clone().generate_constraints(cs.clone()).unwrap();
-        constraint_counts.push(cs.num_constraints());
-        variable_counts.push(cs.num_witness_variables());
         
         // Measure trusted setup
         let setup_start = Instant::now();
-        let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng)
+        let (pk, vk) = Groth16::<E>::circuit_specific_setup(circuit.clone(), &mut rng)
             .expect("Failed to perform trusted setup");
         let setup_time = setup_start.elapsed();
-        setup_times.push(setup_time);
         
         // Measure proof generation
         let prove_start = Instant::now();
-        let proof = Groth16::<Bls12_381>::prove(&pk, circuit.clone(), &mut rng)
+        let proof = Groth16::<E>::prove(&pk, circuit.clone(), &mut rng)
             .expect("Failed to generate proof");
         let prove_time = prove_start.elapsed();
-        prove_times.push(prove_time);
         
         // Measure verification
         let public_inputs = extract_public_inputs(&circuit).unwrap();
         let verify_start = Instant::now();
-        let is_valid = Groth16::<Bls12_381>::verify(&vk, &public_inputs, &proof)
+        let is_valid = Groth16::<E>::verify(&vk, &public_inputs, &proof)
             .expect("Failed to verify proof");
         let verify_time = verify_start.elapsed();
-        verify_times.push(verify_time);
         
         assert!(is_valid, "Proof should be valid");
         
         // Get proof size
         let mut proof_bytes = Vec::new();
         proof.serialize_compressed(&mut proof_bytes).unwrap();
-        proof_sizes.push(proof_bytes.len());
         
+        iterations.push(Iteration {
+            constraints: cs.num_constraints(),
+            witnesses: cs.num_witness_variables(),
+            proof_bytes: proof_bytes.len(),
+            setup: setup_time,
+            prove: prove_time,
+            verify: verify_time,
+        });
         println!("   ✅ Setup: {:?}, Prove: {:?}, Verify: {:?}", 
                  setup_time, prove_time, verify_time);
     }
-    
-    // Calculate statistics
-    let avg_setup = setup_times.iter().sum::<std::time::Duration>() / ITERATIONS as u32;
-    let avg_prove = prove_times.iter().sum::<std::time::Duration>() / ITERATIONS as u32;
-    let avg_verify = verify_times.iter().sum::<std::time::Duration>() / ITERATIONS as u32;
-    
-    let min_setup = *setup_times.iter().min().unwrap();
-    let max_setup = *setup_times.iter().max().unwrap();
-    let min_prove = *prove_times.iter().min().unwrap();
-    let max_prove = *prove_times.iter().max().unwrap();
-    let min_verify = *verify_times.iter().min().unwrap();
-    let max_verify = *verify_times.iter().max().unwrap();
-    
-    let avg_constraints = constraint_counts.iter().sum::<usize>() / ITERATIONS;
-    let avg_variables = variable_counts.iter().sum::<usize>() / ITERATIONS;
-    let avg_proof_size = proof_sizes.iter().sum::<usize>() / ITERATIONS;
-    
-    println!("\n📊 === BENCHMARK RESULTS ({} iterations) ===", ITERATIONS);
-    println!("\n🏗️  Circuit Statistics:");
-    println!("   - Constraints: {} (consistent across iterations)", avg_constraints);
-    println!("   - Variables: {} (consistent across iterations)", avg_variables);
-    println!("   - Proof Size: {} bytes", avg_proof_size);
-    
-    println!("\n⏱️  Timing Analysis:");
-    println!("   TRUSTED SETUP:");
-    println!("     - Average: {:?}", avg_setup);
-    println!("     - Min: {:?}", min_setup);
-    println!("     - Max: {:?}", max_setup);
-    
-    println!("   PROOF GENERATION:");
-    println!("     - Average: {:?}", avg_prove);
-    println!("     - Min: {:?}", min_prove);
-    println!("     - Max: {:?}", max_prove);
-    
-    println!("   PROOF VERIFICATION:");
-    println!("     - Average: {:?}", avg_verify);
-    println!("     - Min: {:?}", min_verify);
-    println!("     - Max: {:?}", max_verify);
-    
-    println!("\n📈 Raw Timing Data (for further analysis):");
-    println!("Setup times (ms): {:?}", 
-             setup_times.iter().map(|t| t.as_millis()).collect::<Vec<_>>());
-    println!("Prove times (ms): {:?}", 
-             prove_times.iter().map(|t| t.as_millis()).collect::<Vec<_>>());
-    println!("Verify times (μs): {:?}", 
-             verify_times.iter().map(|t| t.as_micros()).collect::<Vec<_>>());
-    
-    println!("\n🎯 Performance Summary:");
-    println!("   - Total runtime: {:?}", 
-             setup_times.iter().sum::<std::time::Duration>() + 
-             prove_times.iter().sum::<std::time::Duration>() + 
-             verify_times.iter().sum::<std::time::Duration>());
-    println!("   - Average per iteration: {:?}", 
-             (setup_times.iter().sum::<std::time::Duration>() + 
-              prove_times.iter().sum::<std::time::Duration>() + 
-              verify_times.iter().sum::<std::time::Duration>()) / ITERATIONS as u32);
-}
\ No newline at end of file
+
+    let report = BenchmarkReport::new("elgamal-proof", name, options, &iterations);
+    report.print();
+    let (csv_path, json_path) = report.write(&options.out_dir).expect("Failed to write benchmark report");
+    println!("\n📝 Wrote {} and {}", csv_path.display(), json_path.display());
+    report
+}
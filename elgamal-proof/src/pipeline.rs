@@ -0,0 +1,152 @@
+use ark_crypto_primitives::snark::SNARK;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::pairing::Pairing;
+use ark_groth16::{Groth16, Proof, ProvingKey};
+use ark_relations::r1cs::SynthesisError;
+use ark_std::rand::{rngs::StdRng, Rng, SeedableRng};
+use hash_gadgets::BlockIdHash;
+use rayon::prelude::*;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::circuit::OptimizedElGamalEncryptionCircuit;
+use crate::elgamal::{compute_power, decrypt_elgamal, elgamal_generator};
+
+/// Blocks read from the stream per rayon thread before a chunk is proven
+const BLOCKS_PER_THREAD: usize = 4;
+
+/// One block to prove: (ct, bid, hdk)
+pub type BlockWitness<F> = ([F; 2], [u8; 32], F);
+
+/// Why the pipeline could not prove a block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockProofError {
+    /// The ciphertext does not decrypt under hdk to a message hashing to bid
+    WitnessMismatch,
+    /// Constraint synthesis or proving failed
+    Synthesis(SynthesisError),
+}
+
+impl fmt::Display for BlockProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockProofError::WitnessMismatch => write!(f, "block does not decrypt to its block ID"),
+            BlockProofError::Synthesis(error) => write!(f, "proving failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for BlockProofError {}
+
+/// Proofs of a stream of blocks in stream order, with the wall-clock time they took
+pub struct PipelineOutput<E: Pairing> {
+    pub proofs: Vec<Result<Proof<E>, BlockProofError>>,
+    pub elapsed: Duration,
+}
+
+impl<E: Pairing> PipelineOutput<E> {
+    /// Throughput over every block in the stream, including the ones that failed
+    pub fn blocks_per_second(&self) -> f64 {
+        self.proofs.len() as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Proves a stream of single-block decryptions under one shared proving key on all cores
+///
+/// The stream is read in chunks of `BLOCKS_PER_THREAD` blocks per rayon thread, so memory stays
+/// bounded however long it is, and each chunk is proven in parallel. Every block gets its own
+/// RNG seeded from `rng` in stream order, so the proofs only depend on the seed and not on
+/// scheduling. A block whose witness is wrong gets its own error instead of failing the stream.
+pub fn prove_blocks<E, I, R>(pk: &ProvingKey<E>, blocks: I, hash: BlockIdHash, rng: &mut R) -> PipelineOutput<E>
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+    I: IntoIterator<Item = BlockWitness<E::ScalarField>>,
+    R: Rng,
+{
+    let start = Instant::now();
+    let chunk_size = rayon::current_num_threads() * BLOCKS_PER_THREAD;
+    let mut blocks = blocks.into_iter();
+    let mut proofs = Vec::new();
+    loop {
+        let chunk: Vec<_> = blocks.by_ref().take(chunk_size).map(|block| (block, rng.gen::<[u8; 32]>())).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        proofs.par_extend(
+            chunk
+                .into_par_iter()
+                .map(|(block, seed)| prove_block(pk, block, hash, &mut StdRng::from_seed(seed))),
+        );
+    }
+    PipelineOutput { proofs, elapsed: start.elapsed() }
+}
+
+/// Proves one block, checking natively first that it decrypts to its block ID
+/// Groth16 proving does not check the witness, so skipping this would return an invalid proof.
+fn prove_block<E: Pairing>(
+    pk: &ProvingKey<E>,
+    (ct, bid, hdk): BlockWitness<E::ScalarField>,
+    hash: BlockIdHash,
+    rng: &mut StdRng,
+) -> Result<Proof<E>, BlockProofError>
+where
+    E::ScalarField: Absorb,
+{
+    let message = decrypt_elgamal(&hdk, &ct).ok_or(BlockProofError::WitnessMismatch)?;
+    if hash.block_id(&message) != bid {
+        return Err(BlockProofError::WitnessMismatch);
+    }
+    let hek = compute_power(&elgamal_generator::<E::ScalarField>(), &hdk);
+    let circuit = OptimizedElGamalEncryptionCircuit { ct, hek, bid, hdk, hash };
+    Groth16::<E>::prove(pk, circuit, rng).map_err(BlockProofError::Synthesis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elgamal::setup_elgamal;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::{Field, ToConstraintField};
+
+    /// Random blocks with Poseidon block IDs to keep proving fast
+    fn blocks(count: usize) -> Vec<(BlockWitness<Fr>, Fr)> {
+        let mut rng = StdRng::seed_from_u64(22);
+        (0..count)
+            .map(|_| {
+                let (c1, c2, hdk, message, hek, _bid) = setup_elgamal::<Fr, _>(&mut rng);
+                (([c1, c2], BlockIdHash::Poseidon.block_id(&message), hdk), hek)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pipeline_proves_in_order() {
+        let hash = BlockIdHash::Poseidon;
+        let mut blocks = blocks(6);
+        blocks[3].0 .0[1] += Fr::ONE;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let ((ct, bid, hdk), hek) = blocks[0];
+        let circuit = OptimizedElGamalEncryptionCircuit { ct, hek, bid, hdk, hash };
+        let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(circuit, &mut rng).unwrap();
+
+        let output = prove_blocks(&pk, blocks.iter().map(|(block, _)| *block), hash, &mut rng);
+        assert_eq!(output.proofs.len(), blocks.len());
+        assert!(output.blocks_per_second() > 0.0);
+        for (i, (result, ((ct, bid, _hdk), hek))) in output.proofs.iter().zip(blocks.iter()).enumerate() {
+            if i == 3 {
+                assert_eq!(result, &Err(BlockProofError::WitnessMismatch));
+                continue;
+            }
+            let mut inputs = vec![ct[0], ct[1], *hek];
+            inputs.extend(ToConstraintField::<Fr>::to_field_elements(&bid[..]).unwrap());
+            assert!(Groth16::<Bls12_381>::verify(&vk, &inputs, result.as_ref().unwrap()).unwrap(), "block {}", i);
+        }
+
+        // Proofs depend only on the seed, not on how the blocks were scheduled
+        let first = prove_blocks(&pk, blocks.iter().map(|(block, _)| *block), hash, &mut StdRng::seed_from_u64(1));
+        let second = prove_blocks(&pk, blocks.iter().map(|(block, _)| *block), hash, &mut StdRng::seed_from_u64(1));
+        assert_eq!(first.proofs, second.proofs);
+    }
+}
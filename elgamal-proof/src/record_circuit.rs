@@ -0,0 +1,240 @@
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    fields::fp::FpVar,
+    prelude::*,
+    uint8::UInt8,
+};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::vec::Vec;
+use hash_gadgets::{keccak::Keccak256Gadget, BlockIdHash};
+
+use crate::circuit::OptimizedElGamalEncryptionCircuit;
+
+/// Which values a record proof exposes as public inputs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordPublicInputs {
+    /// Every ciphertext, the public key and every block ID
+    PerBlock,
+    /// Every ciphertext, the public key and recordID = keccak256(abi.encodePacked(blockIDs)), the
+    /// value AccessControlContract stores, in place of the block IDs. The block IDs become witnesses
+    /// bound to it by the in-circuit Keccak.
+    RecordId,
+}
+
+/// Record-level decryption proof: N ciphertext/bid pairs under one key in a single proof
+///
+/// The key is decomposed into bits and bound to hek once, so each additional block only
+/// pays for its own exponentiation and SHA256.
+#[derive(Clone)]
+pub struct RecordElGamalEncryptionCircuit<const N: usize, F: PrimeField = Fr> {
+    pub cts: [[F; 2]; N],     // Ciphertexts (c1, c2) of every block in the record
+    pub hek: F,               // Public key (hek = g^hdk)
+    pub bids: [[u8; 32]; N],   // Block IDs, in the same order as the ciphertexts
+    pub record_id: [u8; 32],   // Record ID over the packed block IDs (used in RecordId mode)
+    pub hdk: F,               // Private key (Hierarchical Derived Key)
+    pub hash: BlockIdHash,     // Hash function deriving each bid from its message
+    pub public_inputs: RecordPublicInputs,
+}
+
+impl<const N: usize, F: PrimeField> ConstraintSynthesizer<F> for RecordElGamalEncryptionCircuit<N, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // In RecordId mode the record ID replaces the block IDs as public input. The ciphertexts
+        // and hek stay public in both modes, otherwise the proof would not say which key and
+        // which delivered ciphertexts it is about.
+        let bid_mode = match self.public_inputs {
+            RecordPublicInputs::PerBlock => AllocationMode::Input,
+            RecordPublicInputs::RecordId => AllocationMode::Witness,
+        };
+        let record_id_var = match self.public_inputs {
+            RecordPublicInputs::PerBlock => None,
+            RecordPublicInputs::RecordId => Some(UInt8::<F>::new_input_vec(cs.clone(), &self.record_id)?),
+        };
+
+        // Allocate all ciphertexts
+        let ct_vars = self
+            .cts
+            .iter()
+            .map(|ct| {
+                Ok([
+                    FpVar::<F>::new_input(cs.clone(), || Ok(ct[0]))?,
+                    FpVar::<F>::new_input(cs.clone(), || Ok(ct[1]))?,
+                ])
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        // Allocate the buyer's published public key
+        let hek_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.hek))?;
+
+        // Allocate all block IDs
+        let bid_vars = self
+            .bids
+            .iter()
+            .map(|bid| match bid_mode {
+                AllocationMode::Input => UInt8::<F>::new_input_vec(cs.clone(), bid),
+                _ => UInt8::<F>::new_witness_vec(cs.clone(), bid),
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        // recordID = keccak256(abi.encodePacked(blockIDs)): the packed encoding of bytes32[]
+        // is the plain concatenation of the 32-byte block IDs
+        if let Some(record_id_var) = record_id_var {
+            let packed_bids = bid_vars.concat();
+            let record_hash = Keccak256Gadget::digest(&packed_bids)?;
+            record_hash.enforce_equal(&record_id_var)?;
+        }
+
+        // Allocate private key bits as witness once for every block
+        let hdk_bits = OptimizedElGamalEncryptionCircuit::alloc_exponent_bits(cs.clone(), self.hdk)?;
+
+        // Key binding: hek = g^hdk
+        OptimizedElGamalEncryptionCircuit::enforce_key_binding(&hek_var, &hdk_bits)?;
+
+        // Every block must decrypt under hdk to a message hashing to its block ID
+        for (ct_var, bid_var) in ct_vars.iter().zip(bid_vars.iter()) {
+            OptimizedElGamalEncryptionCircuit::enforce_decrypts_to_bid(&ct_var[0], &ct_var[1], &hdk_bits, bid_var, self.hash)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elgamal::{compute_record_id, encrypt_elgamal, setup_elgamal, setup_record_elgamal};
+    use ark_bls12_381::Bls12_381;
+    use ark_crypto_primitives::snark::SNARK;
+    use ark_ff::UniformRand;
+    use ark_groth16::Groth16;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_record_circuit_correctness() {
+        let mut rng = test_rng();
+        let (cts, hdk, _messages, hek, bids) = setup_record_elgamal::<Fr, _, 3>(&mut rng);
+        let record_id = compute_record_id(&bids);
+
+        let circuit = RecordElGamalEncryptionCircuit::<3> {
+            cts,
+            hek,
+            bids,
+            record_id,
+            hdk,
+            hash: BlockIdHash::Sha256,
+            public_inputs: RecordPublicInputs::PerBlock,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        println!("Record (N=3) constraints: {}", cs.num_constraints());
+        assert!(cs.is_satisfied().unwrap(), "Record circuit should be satisfied");
+    }
+
+    #[test]
+    fn test_record_circuit_rejects_single_bad_block() {
+        let mut rng = test_rng();
+        let (mut cts, hdk, _messages, hek, bids) = setup_record_elgamal::<Fr, _, 3>(&mut rng);
+        let record_id = compute_record_id(&bids);
+
+        // Corrupt the last block only
+        cts[2][1] = Fr::rand(&mut rng);
+        let circuit = RecordElGamalEncryptionCircuit::<3> {
+            cts,
+            hek,
+            bids,
+            record_id,
+            hdk,
+            hash: BlockIdHash::Sha256,
+            public_inputs: RecordPublicInputs::PerBlock,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap(), "A corrupted block must fail the whole record");
+    }
+
+    #[test]
+    fn test_record_id_mode() {
+        let mut rng = test_rng();
+        let (cts, hdk, _messages, hek, bids) = setup_record_elgamal::<Fr, _, 2>(&mut rng);
+        let record_id = compute_record_id(&bids);
+
+        let circuit = RecordElGamalEncryptionCircuit::<2> {
+            cts,
+            hek,
+            bids,
+            record_id,
+            hdk,
+            hash: BlockIdHash::Sha256,
+            public_inputs: RecordPublicInputs::RecordId,
+        };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        println!("Record (N=2, recordID) constraints: {}", cs.num_constraints());
+        assert!(cs.is_satisfied().unwrap(), "RecordId mode should be satisfied");
+        // The constant one, the 32-byte record ID packed into two field elements, two
+        // ciphertexts and hek
+        assert_eq!(cs.num_instance_variables(), 1 + 2 + 2 * 2 + 1);
+
+        // A record ID over different block IDs must be rejected
+        let mut other_bids = bids;
+        other_bids.swap(0, 1);
+        let wrong = RecordElGamalEncryptionCircuit::<2> {
+            record_id: compute_record_id(&other_bids),
+            ..circuit
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        wrong.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap(), "Record ID must match the packed block IDs");
+    }
+
+    #[test]
+    fn test_record_id_mode_binds_key_and_ciphertexts() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let (cts, hdk, messages, hek, bids) = setup_record_elgamal::<Fr, _, 2>(&mut rng);
+        let record_id = compute_record_id(&bids);
+        let circuit = |cts: [[Fr; 2]; 2], hek: Fr, hdk: Fr| RecordElGamalEncryptionCircuit::<2> {
+            cts,
+            hek,
+            bids,
+            record_id,
+            hdk,
+            hash: BlockIdHash::Sha256,
+            public_inputs: RecordPublicInputs::RecordId,
+        };
+
+        // Public inputs: the record ID as two field elements, then every (c1, c2), then hek
+        let buyer = circuit(cts, hek, hdk);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        buyer.clone().generate_constraints(cs.clone()).unwrap();
+        let buyer_inputs = cs.borrow().unwrap().instance_assignment[1..].to_vec();
+
+        let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(buyer.clone(), &mut rng).unwrap();
+        let proof = Groth16::<Bls12_381>::prove(&pk, buyer, &mut rng).unwrap();
+        assert!(Groth16::<Bls12_381>::verify(&vk, &buyer_inputs, &proof).unwrap());
+
+        // A seller who knows the blocks encrypts them under a key pair of their own
+        let (_, _, seller_hdk, _, seller_hek, _) = setup_elgamal::<Fr, _>(&mut rng);
+        let seller_cts = messages.map(|message| {
+            let (c1, c2) = encrypt_elgamal(&seller_hek, &message, &Fr::rand(&mut rng));
+            [c1, c2]
+        });
+
+        let mut swapped_hek = buyer_inputs.clone();
+        swapped_hek[6] = seller_hek;
+        assert!(!Groth16::<Bls12_381>::verify(&vk, &swapped_hek, &proof).unwrap(), "Proof must be bound to hek");
+
+        let mut swapped_ct = buyer_inputs.clone();
+        swapped_ct[2..4].copy_from_slice(&seller_cts[0]);
+        assert!(!Groth16::<Bls12_381>::verify(&vk, &swapped_ct, &proof).unwrap(), "Proof must be bound to the ciphertexts");
+
+        // The seller's proof is valid for its own key, but not for the buyer's hek and ciphertexts
+        let seller_proof = Groth16::<Bls12_381>::prove(&pk, circuit(seller_cts, seller_hek, seller_hdk), &mut rng).unwrap();
+        assert!(!Groth16::<Bls12_381>::verify(&vk, &buyer_inputs, &seller_proof).unwrap(), "Another key's proof must not verify");
+    }
+}
@@ -0,0 +1,120 @@
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, prelude::*, uint8::UInt8};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use hash_gadgets::BlockIdHash;
+
+use crate::circuit::OptimizedElGamalEncryptionCircuit;
+use crate::elgamal::elgamal_generator;
+
+/// Proof of correct re-encryption, produced by the seller after payment
+///
+/// Shows that (d1, d2) under the buyer's public key encrypts the same plaintext as (c1, c2)
+/// under hek, and that this plaintext hashes to bid. The seller proves knowledge of hdk and
+/// of the new nonce y; the plaintext is only an intermediate value of the circuit.
+#[derive(Clone)]
+pub struct ElGamalReEncryptionCircuit<F: PrimeField = Fr> {
+    pub ct: [F; 2],        // Original ciphertext (c1, c2)
+    pub hek: F,            // Seller's public key (hek = g^hdk)
+    pub buyer_ct: [F; 2],  // Re-encrypted ciphertext (d1, d2)
+    pub buyer_pk: F,       // Buyer's public key from AccessControlContract
+    pub bid: [u8; 32],     // Block ID as a hash of the message
+    pub hdk: F,            // Private key of the original ciphertext
+    pub y: F,              // Private nonce of the re-encryption
+    pub hash: BlockIdHash, // Hash function deriving bid from the message
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for ElGamalReEncryptionCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Allocate both ciphertexts, both public keys and the block ID as public inputs
+        let c1_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.ct[0]))?;
+        let c2_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.ct[1]))?;
+        let hek_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.hek))?;
+        let d1_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.buyer_ct[0]))?;
+        let d2_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.buyer_ct[1]))?;
+        let buyer_pk_var = FpVar::<F>::new_input(cs.clone(), || Ok(self.buyer_pk))?;
+        let bid_var = UInt8::<F>::new_input_vec(cs.clone(), &self.bid)?;
+
+        // Allocate key and nonce bits as witnesses
+        let hdk_bits = OptimizedElGamalEncryptionCircuit::alloc_exponent_bits(cs.clone(), self.hdk)?;
+        let y_bits = OptimizedElGamalEncryptionCircuit::alloc_exponent_bits(cs.clone(), self.y)?;
+
+        // Key binding: hek = g^hdk
+        OptimizedElGamalEncryptionCircuit::enforce_key_binding(&hek_var, &hdk_bits)?;
+
+        // Decryption: m = c2 / c1^hdk
+        let s = OptimizedElGamalEncryptionCircuit::variable_base_exponentiation(&c1_var, &hdk_bits)?;
+        let m = &c2_var * s.inverse()?;
+
+        // Re-encryption: d1 = g^y and d2 = m * buyer_pk^y
+        let expected_d1 = OptimizedElGamalEncryptionCircuit::fixed_base_exponentiation(elgamal_generator(), &y_bits)?;
+        expected_d1.enforce_equal(&d1_var)?;
+        let buyer_s = OptimizedElGamalEncryptionCircuit::variable_base_exponentiation(&buyer_pk_var, &y_bits)?;
+        m.mul_equals(&buyer_s, &d2_var)?;
+
+        // H(m) = bid
+        let m_bytes = OptimizedElGamalEncryptionCircuit::field_to_bytes_optimized(&m)?;
+        let hash_bytes = self.hash.block_id_gadget(&m_bytes)?;
+        OptimizedElGamalEncryptionCircuit::batch_equality_check(&hash_bytes[..32], &bid_var)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elgamal::{encrypt_elgamal, reencrypt_elgamal, setup_elgamal, setup_reencryption_proof};
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_std::test_rng;
+
+    fn honest_circuit() -> ElGamalReEncryptionCircuit {
+        let mut rng = test_rng();
+        let (ct, hdk, hek, buyer_ct, y, buyer_pk, _message, bid) = setup_reencryption_proof(&mut rng);
+        ElGamalReEncryptionCircuit { ct, hek, buyer_ct, buyer_pk, bid, hdk, y, hash: BlockIdHash::Sha256 }
+    }
+
+    #[test]
+    fn test_reencryption_circuit_correctness() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        honest_circuit().generate_constraints(cs.clone()).unwrap();
+        println!("Re-encryption constraints: {}", cs.num_constraints());
+        assert!(cs.is_satisfied().unwrap(), "Honest re-encryption should satisfy the circuit");
+    }
+
+    #[test]
+    fn test_reencryption_rejects_other_plaintext_or_key() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let honest = honest_circuit();
+
+        // A fresh encryption of another message under the buyer's key, with its own nonce
+        let y = Fr::rand(&mut rng);
+        let (d1, d2) = encrypt_elgamal(&honest.buyer_pk, &Fr::rand(&mut rng), &y);
+        let (_, _, _, _, other_pk, _) = setup_elgamal::<Fr, _>(&mut rng);
+
+        let tampered = vec![
+            ElGamalReEncryptionCircuit { buyer_ct: [d1, d2], y, ..honest.clone() },
+            ElGamalReEncryptionCircuit { buyer_pk: other_pk, ..honest.clone() },
+            ElGamalReEncryptionCircuit { buyer_ct: [honest.buyer_ct[0], honest.buyer_ct[1] + Fr::from(1u64)], ..honest.clone() },
+            ElGamalReEncryptionCircuit { y: Fr::rand(&mut rng), ..honest.clone() },
+            ElGamalReEncryptionCircuit { hdk: Fr::rand(&mut rng), ..honest.clone() },
+            ElGamalReEncryptionCircuit { bid: [0u8; 32], ..honest.clone() },
+        ];
+
+        for circuit in tampered {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert!(!cs.is_satisfied().unwrap(), "Tampered re-encryption must be rejected");
+        }
+    }
+
+    #[test]
+    fn test_reencrypt_undecryptable_ciphertext() {
+        let honest = honest_circuit();
+        // c1 = 0 makes c1^hdk zero, which has no inverse
+        let ct = [Fr::from(0u64), honest.ct[1]];
+        assert_eq!(reencrypt_elgamal(&honest.hdk, &ct, &honest.buyer_pk, &honest.y), None);
+    }
+}
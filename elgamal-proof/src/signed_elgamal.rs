@@ -0,0 +1,151 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::rand::Rng;
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::elgamal::{compute_block_id, compute_power, elgamal_generator, encrypt_elgamal, setup_elgamal};
+
+/// Domain separator of the Fiat-Shamir challenge
+const CHALLENGE_DOMAIN: &[u8] = b"REUAnalysis/signed-elgamal/nonce-pok/v1";
+
+/// Fiat-Shamir Schnorr proof of knowledge of the nonce y with c1 = g^y
+///
+/// Exponents live modulo p - 1, the order of the multiplicative group; since p - 1 < p,
+/// the response is stored as a field element and read back as an integer exponent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceProof<F: PrimeField = Fr> {
+    pub commitment: F, // A = g^r
+    pub response: F,   // z = r + e * y mod (p - 1)
+}
+
+/// Signed ElGamal ciphertext: (c1, c2) together with a proof of knowledge of its nonce
+///
+/// The challenge hashes the public key and both ciphertext components, so a ciphertext
+/// derived from another one, e.g. (c1, k * c2), no longer matches its proof, and whoever
+/// produced a valid ciphertext must already know the message it encrypts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignedCiphertext<F: PrimeField = Fr> {
+    pub ct: [F; 2],          // Ciphertext (c1, c2)
+    pub proof: NonceProof<F>, // Proof of knowledge of y
+}
+
+/// e = H(domain, g, hek, c1, c2, A), reduced into the field
+fn challenge<F: PrimeField>(hek: &F, ct: &[F; 2], commitment: &F) -> F {
+    let mut hasher = Sha256::new();
+    hasher.update(CHALLENGE_DOMAIN);
+    for value in [elgamal_generator::<F>(), *hek, ct[0], ct[1], *commitment] {
+        hasher.update(value.into_bigint().to_bytes_le());
+    }
+    F::from_le_bytes_mod_order(&hasher.finalize())
+}
+
+/// Proves knowledge of the nonce y used to produce `ct` under `hek`
+pub fn prove_nonce_knowledge<F: PrimeField, R: Rng>(hek: &F, ct: &[F; 2], y: &F, rng: &mut R) -> NonceProof<F> {
+    let g = elgamal_generator::<F>(); // Generator
+    let r = F::rand(rng); // Commitment randomness
+    let commitment = compute_power(&g, &r);
+    let e = challenge(hek, ct, &commitment);
+
+    // z = r + e * y over the integers modulo the group order p - 1
+    let order = BigUint::from_bytes_le(&F::MODULUS.to_bytes_le()) - 1u64;
+    let [r, e, y]: [BigUint; 3] = [r.into(), e.into(), (*y).into()];
+    let z = (r + e * y) % order;
+
+    NonceProof { commitment, response: F::from(z) }
+}
+
+/// Checks the nonce proof of a signed ciphertext: g^z = A * c1^e
+/// Run this before decrypting or proving anything about the ciphertext.
+pub fn verify_signed_ciphertext<F: PrimeField>(hek: &F, signed: &SignedCiphertext<F>) -> bool {
+    let g = elgamal_generator::<F>(); // Generator
+    let NonceProof { commitment, response } = signed.proof;
+    let e = challenge(hek, &signed.ct, &commitment);
+    compute_power(&g, &response) == commitment * compute_power(&signed.ct[0], &e)
+}
+
+/// Encrypts `message` under `hek` and attaches the proof of knowledge of the nonce
+/// Returns the signed ciphertext and the nonce y.
+pub fn encrypt_signed_elgamal<F: PrimeField, R: Rng>(hek: &F, message: &F, rng: &mut R) -> (SignedCiphertext<F>, F) {
+    let y = F::rand(rng); // Random nonce
+    let (c1, c2) = encrypt_elgamal(hek, message, &y);
+    let proof = prove_nonce_knowledge(hek, &[c1, c2], &y, rng);
+    (SignedCiphertext { ct: [c1, c2], proof }, y)
+}
+
+/// Decrypts a signed ciphertext, or returns `None` if its proof does not verify
+pub fn decrypt_signed_elgamal<F: PrimeField>(hdk: &F, hek: &F, signed: &SignedCiphertext<F>) -> Option<F> {
+    if !verify_signed_ciphertext(hek, signed) {
+        return None;
+    }
+    let s = compute_power(&signed.ct[0], hdk); // s = c1^hdk
+    Some(signed.ct[1] * s.inverse()?) // m = c2 * s^(-1)
+}
+
+/// Generates ElGamal parameters and a signed encryption of a random message
+/// Returns (signed ciphertext, hdk, message, hek, bid); `signed.ct` feeds the decryption circuits unchanged.
+pub fn setup_signed_elgamal<F: PrimeField + Absorb, R: Rng>(rng: &mut R) -> (SignedCiphertext<F>, F, F, F, [u8; 32]) {
+    let (_, _, hdk, message, hek, _bid) = setup_elgamal(rng);
+    let (signed, _y) = encrypt_signed_elgamal(&hek, &message, rng);
+
+    // Verify decryption works correctly
+    assert_eq!(decrypt_signed_elgamal(&hdk, &hek, &signed), Some(message), "Decryption failed - implementation error!");
+
+    let bid = compute_block_id(&message);
+    (signed, hdk, message, hek, bid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::{Field, UniformRand};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_signed_ciphertext_verifies_and_decrypts() {
+        let mut rng = test_rng();
+        let (signed, hdk, message, hek, _bid) = setup_signed_elgamal::<Fr, _>(&mut rng);
+        assert!(verify_signed_ciphertext(&hek, &signed));
+        assert_eq!(decrypt_signed_elgamal(&hdk, &hek, &signed), Some(message));
+
+        let (signed, _hdk, _message, hek, _bid) = setup_signed_elgamal::<ark_bn254::Fr, _>(&mut rng);
+        assert!(verify_signed_ciphertext(&hek, &signed));
+    }
+
+    #[test]
+    fn test_mauled_ciphertext_is_rejected() {
+        let mut rng = test_rng();
+        let (signed, hdk, message, hek, _bid) = setup_signed_elgamal::<Fr, _>(&mut rng);
+        let factor = Fr::from(3u64);
+
+        // (c1, k * c2) decrypts to k * m under plain ElGamal, which is why it must be rejected
+        let mauled = SignedCiphertext { ct: [signed.ct[0], factor * signed.ct[1]], ..signed };
+        let s = compute_power(&mauled.ct[0], &hdk);
+        assert_eq!(mauled.ct[1] * s.inverse().unwrap(), factor * message);
+        assert!(!verify_signed_ciphertext(&hek, &mauled));
+        assert_eq!(decrypt_signed_elgamal(&hdk, &hek, &mauled), None);
+
+        // Re-randomising with g^t and hek^t keeps the plaintext but breaks the proof
+        let t = Fr::rand(&mut rng);
+        let (g_t, hek_t) = encrypt_elgamal(&hek, &Fr::from(1u64), &t);
+        let rerandomised = SignedCiphertext { ct: [signed.ct[0] * g_t, signed.ct[1] * hek_t], ..signed };
+        assert!(!verify_signed_ciphertext(&hek, &rerandomised));
+    }
+
+    #[test]
+    fn test_proof_is_bound_to_its_ciphertext_and_key() {
+        let mut rng = test_rng();
+        let (signed, _hdk, _message, hek, _bid) = setup_signed_elgamal::<Fr, _>(&mut rng);
+        let (other, _y) = encrypt_signed_elgamal(&hek, &Fr::rand(&mut rng), &mut rng);
+        let (_, _, _, other_hek, _) = setup_signed_elgamal::<Fr, _>(&mut rng);
+
+        // Replaying a proof on another ciphertext, or against another key
+        assert!(!verify_signed_ciphertext(&hek, &SignedCiphertext { proof: other.proof, ..signed }));
+        assert!(!verify_signed_ciphertext(&other_hek, &signed));
+
+        // A forged response
+        let forged = NonceProof { response: signed.proof.response + Fr::from(1u64), ..signed.proof };
+        assert!(!verify_signed_ciphertext(&hek, &SignedCiphertext { proof: forged, ..signed }));
+    }
+}
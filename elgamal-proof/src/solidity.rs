@@ -0,0 +1,325 @@
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_std::vec::Vec;
+use hash_gadgets::keccak::keccak256;
+
+/// Solidity signature of the verifier's entry point, hashed into the calldata selector
+pub const VERIFY_PROOF_SIGNATURE: &str = "verifyProof(uint256[8],uint256[])";
+
+/// BN254 pairing library using the EIP-196/197 precompiles, as in ZoKrates' generated verifiers
+///
+/// G2 coordinates are stored as [c0, c1] and swapped into the precompile's (c1, c0) order.
+const PAIRING_LIBRARY: &str = r#"library Pairing {
+    struct G1Point {
+        uint X;
+        uint Y;
+    }
+    // Encoding of field elements is: X[1] * i + X[0]
+    struct G2Point {
+        uint[2] X;
+        uint[2] Y;
+    }
+    /// @return the negation of p, i.e. p.addition(p.negate()) should be zero.
+    function negate(G1Point memory p) pure internal returns (G1Point memory) {
+        // The prime q in the base field F_q for G1
+        uint q = 21888242871839275222246405745257275088696311157297823662689037894645226208583;
+        if (p.X == 0 && p.Y == 0)
+            return G1Point(0, 0);
+        return G1Point(p.X, q - (p.Y % q));
+    }
+    /// @return r the sum of two points of G1
+    function addition(G1Point memory p1, G1Point memory p2) internal view returns (G1Point memory r) {
+        uint[4] memory input;
+        input[0] = p1.X;
+        input[1] = p1.Y;
+        input[2] = p2.X;
+        input[3] = p2.Y;
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 6, input, 0xc0, r, 0x60)
+            // Use "invalid" to make gas estimation work
+            switch success case 0 { invalid() }
+        }
+        require(success);
+    }
+    /// @return r the product of a point on G1 and a scalar
+    function scalar_mul(G1Point memory p, uint s) internal view returns (G1Point memory r) {
+        uint[3] memory input;
+        input[0] = p.X;
+        input[1] = p.Y;
+        input[2] = s;
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 7, input, 0x80, r, 0x60)
+            // Use "invalid" to make gas estimation work
+            switch success case 0 { invalid() }
+        }
+        require(success);
+    }
+    /// @return the result of computing the pairing check
+    /// e(a1, a2) * e(b1, b2) * e(c1, c2) * e(d1, d2) == 1
+    function pairingProd4(
+            G1Point memory a1, G2Point memory a2,
+            G1Point memory b1, G2Point memory b2,
+            G1Point memory c1, G2Point memory c2,
+            G1Point memory d1, G2Point memory d2
+    ) internal view returns (bool) {
+        G1Point[4] memory p1;
+        G2Point[4] memory p2;
+        p1[0] = a1;
+        p1[1] = b1;
+        p1[2] = c1;
+        p1[3] = d1;
+        p2[0] = a2;
+        p2[1] = b2;
+        p2[2] = c2;
+        p2[3] = d2;
+        uint[24] memory input;
+        for (uint i = 0; i < 4; i++)
+        {
+            input[i * 6 + 0] = p1[i].X;
+            input[i * 6 + 1] = p1[i].Y;
+            input[i * 6 + 2] = p2[i].X[1];
+            input[i * 6 + 3] = p2[i].X[0];
+            input[i * 6 + 4] = p2[i].Y[1];
+            input[i * 6 + 5] = p2[i].Y[0];
+        }
+        uint[1] memory out;
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 8, input, 0x300, out, 0x20)
+            // Use "invalid" to make gas estimation work
+            switch success case 0 { invalid() }
+        }
+        require(success);
+        return out[0] != 0;
+    }
+}
+"#;
+
+/// Big-endian 32-byte encoding of a field element, i.e. its `uint256` ABI word
+pub fn to_word<F: PrimeField>(value: &F) -> [u8; 32] {
+    let bytes = value.into_bigint().to_bytes_be();
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+/// Solidity `uint256` literal of a base field element
+fn uint256(value: &Fq) -> String {
+    format!("uint256(0x{})", hex::encode(to_word(value)))
+}
+
+fn g1_point(point: &G1Affine) -> String {
+    format!("Pairing.G1Point({}, {})", uint256(&point.x), uint256(&point.y))
+}
+
+fn g2_point(point: &G2Affine) -> String {
+    format!(
+        "Pairing.G2Point([{}, {}], [{}, {}])",
+        uint256(&point.x.c0),
+        uint256(&point.x.c1),
+        uint256(&point.y.c0),
+        uint256(&point.y.c1)
+    )
+}
+
+/// Renders a Solidity Groth16 verifier contract with `vk` embedded as constants
+///
+/// The contract exposes `verifyProof(uint256[8] proof, uint256[] input)`; use
+/// `encode_calldata` to build a call from an arkworks proof.
+pub fn render_verifier(vk: &VerifyingKey<Bn254>, contract_name: &str) -> String {
+    let num_inputs = vk.gamma_abc_g1.len() - 1;
+    let mut gamma_abc = String::new();
+    for (i, point) in vk.gamma_abc_g1.iter().enumerate() {
+        gamma_abc.push_str(&format!("        vk.gamma_abc[{}] = {};\n", i, g1_point(point)));
+    }
+
+    let mut contract = String::from("// SPDX-License-Identifier: MIT\n// Generated by elgamal-proof from a BN254 Groth16 verifying key.\npragma solidity ^0.8.0;\n\n");
+    contract.push_str(PAIRING_LIBRARY);
+    contract.push_str(&format!(
+        r#"
+contract {name} {{
+    using Pairing for *;
+    struct VerifyingKey {{
+        Pairing.G1Point alpha;
+        Pairing.G2Point beta;
+        Pairing.G2Point gamma;
+        Pairing.G2Point delta;
+        Pairing.G1Point[] gamma_abc;
+    }}
+    struct Proof {{
+        Pairing.G1Point a;
+        Pairing.G2Point b;
+        Pairing.G1Point c;
+    }}
+    function verifyingKey() pure internal returns (VerifyingKey memory vk) {{
+        vk.alpha = {alpha};
+        vk.beta = {beta};
+        vk.gamma = {gamma};
+        vk.delta = {delta};
+        vk.gamma_abc = new Pairing.G1Point[]({gamma_abc_len});
+{gamma_abc}    }}
+    function verify(uint[] memory input, Proof memory proof) internal view returns (bool) {{
+        uint256 snark_scalar_field = {scalar_field};
+        VerifyingKey memory vk = verifyingKey();
+        require(input.length + 1 == vk.gamma_abc.length, "Wrong number of public inputs");
+        // Compute the linear combination vk_x
+        Pairing.G1Point memory vk_x = vk.gamma_abc[0];
+        for (uint i = 0; i < input.length; i++) {{
+            require(input[i] < snark_scalar_field, "Public input not in the scalar field");
+            vk_x = Pairing.addition(vk_x, Pairing.scalar_mul(vk.gamma_abc[i + 1], input[i]));
+        }}
+        return Pairing.pairingProd4(
+             proof.a, proof.b,
+             Pairing.negate(vk_x), vk.gamma,
+             Pairing.negate(proof.c), vk.delta,
+             Pairing.negate(vk.alpha), vk.beta);
+    }}
+    /// @param proof [a.X, a.Y, b.X[0], b.X[1], b.Y[0], b.Y[1], c.X, c.Y]
+    /// @param input the {num_inputs} public inputs in circuit allocation order
+    function verifyProof(uint256[8] calldata proof, uint256[] calldata input) external view returns (bool) {{
+        Proof memory p = Proof(
+            Pairing.G1Point(proof[0], proof[1]),
+            Pairing.G2Point([proof[2], proof[3]], [proof[4], proof[5]]),
+            Pairing.G1Point(proof[6], proof[7])
+        );
+        return verify(input, p);
+    }}
+}}
+"#,
+        name = contract_name,
+        alpha = g1_point(&vk.alpha_g1),
+        beta = g2_point(&vk.beta_g2),
+        gamma = g2_point(&vk.gamma_g2),
+        delta = g2_point(&vk.delta_g2),
+        gamma_abc_len = vk.gamma_abc_g1.len(),
+        gamma_abc = gamma_abc,
+        scalar_field = Fr::MODULUS,
+        num_inputs = num_inputs,
+    ));
+    contract
+}
+
+/// ABI words of a proof in the `uint256[8]` layout expected by `verifyProof`
+pub fn proof_words(proof: &Proof<Bn254>) -> [[u8; 32]; 8] {
+    [
+        to_word(&proof.a.x),
+        to_word(&proof.a.y),
+        to_word(&proof.b.x.c0),
+        to_word(&proof.b.x.c1),
+        to_word(&proof.b.y.c0),
+        to_word(&proof.b.y.c1),
+        to_word(&proof.c.x),
+        to_word(&proof.c.y),
+    ]
+}
+
+/// ABI-encodes a call to `verifyProof(proof, public_inputs)`, selector included
+///
+/// The static `uint256[8]` is encoded in place, followed by the offset, length and
+/// elements of the dynamic input array.
+pub fn encode_calldata(proof: &Proof<Bn254>, public_inputs: &[Fr]) -> Vec<u8> {
+    let selector = &keccak256(VERIFY_PROOF_SIGNATURE.as_bytes())[..4];
+    let mut calldata = selector.to_vec();
+    for word in proof_words(proof) {
+        calldata.extend_from_slice(&word);
+    }
+
+    // Head of the dynamic array: offset past the 8 proof words and this offset word
+    calldata.extend_from_slice(&to_word(&Fr::from(9u64 * 32)));
+    calldata.extend_from_slice(&to_word(&Fr::from(public_inputs.len() as u64)));
+    for input in public_inputs {
+        calldata.extend_from_slice(&to_word(input));
+    }
+    calldata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::ElGamalCorrectEncryptionCircuit;
+    use crate::elgamal::setup_encryption_proof;
+    use ark_crypto_primitives::snark::SNARK;
+    use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+    use ark_ff::One;
+    use ark_groth16::Groth16;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use hash_gadgets::BlockIdHash;
+
+    fn fq(word: &[u8]) -> Fq {
+        Fq::from_be_bytes_mod_order(word)
+    }
+
+    /// Runs the contract's `verify` on decoded calldata, with the same equation and point encoding
+    fn verify_calldata(vk: &VerifyingKey<Bn254>, calldata: &[u8]) -> bool {
+        assert_eq!(&calldata[..4], &keccak256(VERIFY_PROOF_SIGNATURE.as_bytes())[..4]);
+        let words: Vec<&[u8]> = calldata[4..].chunks(32).collect();
+        let g2 = |w: &[&[u8]]| {
+            G2Affine::new(
+                ark_bn254::Fq2::new(fq(w[0]), fq(w[1])),
+                ark_bn254::Fq2::new(fq(w[2]), fq(w[3])),
+            )
+        };
+        let a = G1Affine::new(fq(words[0]), fq(words[1]));
+        let b = g2(&words[2..6]);
+        let c = G1Affine::new(fq(words[6]), fq(words[7]));
+        assert_eq!(fq(words[8]), Fq::from(9u64 * 32));
+
+        let len = fq(words[9]).into_bigint().as_ref()[0] as usize;
+        let mut vk_x = vk.gamma_abc_g1[0].into_group();
+        for (i, word) in words[10..10 + len].iter().enumerate() {
+            let input = Fr::from_be_bytes_mod_order(word);
+            assert_eq!(&to_word(&input)[..], *word, "input not in the scalar field");
+            vk_x += vk.gamma_abc_g1[i + 1] * input;
+        }
+
+        let result = Bn254::multi_pairing(
+            [a, -vk_x.into_affine(), -c, -vk.alpha_g1],
+            [b, vk.gamma_g2, vk.delta_g2, vk.beta_g2],
+        );
+        result.0 == <Bn254 as Pairing>::TargetField::one()
+    }
+
+    #[test]
+    fn test_pairing_library_matches_precompile_generator() {
+        // The G2 generator as written in ZoKrates' P2(): X = [c0, c1]
+        let g2 = G2Affine::generator();
+        assert_eq!(g2.x.c0.to_string(), "10857046999023057135944570762232829481370756359578518086990519993285655852781");
+        assert_eq!(g2.x.c1.to_string(), "11559732032986387107991004021392285783925812861821192530917403151452391805634");
+    }
+
+    #[test]
+    fn test_calldata_verifies_like_the_contract() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let (c1, c2, y, message, hek, bid) = setup_encryption_proof::<Fr, _>(&mut rng);
+        let circuit = ElGamalCorrectEncryptionCircuit { ct: [c1, c2], hek, bid, y, message, hash: BlockIdHash::Sha256 };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        let public_inputs = cs.borrow().unwrap().instance_assignment[1..].to_vec();
+
+        let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        let proof = Groth16::<Bn254>::prove(&pk, circuit, &mut rng).unwrap();
+
+        let calldata = encode_calldata(&proof, &public_inputs);
+        assert_eq!(calldata.len(), 4 + 32 * (10 + public_inputs.len()));
+        assert!(verify_calldata(&vk, &calldata));
+
+        // AccessControlContract.packBlockID: 31 little-endian bytes, then the last byte
+        let low = bid[..31].iter().rev().fold(Fr::from(0u64), |acc, byte| acc * Fr::from(256u64) + Fr::from(*byte));
+        assert_eq!(public_inputs[3..], [low, Fr::from(bid[31])]);
+
+        // Tampering with a public input must fail the on-chain check
+        let mut tampered = public_inputs.clone();
+        tampered[0] += Fr::one();
+        assert!(!verify_calldata(&vk, &encode_calldata(&proof, &tampered)));
+
+        let contract = render_verifier(&vk, "ElGamalEncryptionVerifier");
+        assert!(contract.contains("contract ElGamalEncryptionVerifier {"));
+        assert!(contract.contains(&format!("new Pairing.G1Point[]({})", public_inputs.len() + 1)));
+        assert!(contract.contains(&format!("vk.alpha = {};", g1_point(&vk.alpha_g1))));
+    }
+}
@@ -0,0 +1,309 @@
+use ark_bls12_381::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ed_on_bls12_381::{EdwardsAffine, Fr as JubjubScalar};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::Rng;
+use hash_gadgets::BlockIdHash;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::elgamal::encrypt_ec_elgamal;
+
+/// Domain separator of the Chaum-Pedersen challenge
+const DLEQ_DOMAIN: &[u8] = b"REUAnalysis/threshold-elgamal/dleq/v1";
+
+/// t-of-n threshold key for Jubjub EC-ElGamal
+///
+/// hdk is Shamir-shared over Jubjub's prime scalar field. The Fr variant cannot be shared
+/// this way: its group has the composite order p - 1, where Lagrange coefficients need not exist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThresholdKey {
+    pub hek: EdwardsAffine,                    // Public key (hek = hdk * G)
+    pub verification_keys: Vec<EdwardsAffine>, // hek_i = hdk_i * G of custodian i, in index order
+    pub threshold: usize,                      // Shares needed to decrypt
+}
+
+/// Secret share hdk_i = f(i) held by custodian i (indices start at 1)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyShare {
+    pub index: u64,
+    pub share: JubjubScalar,
+}
+
+/// Chaum-Pedersen proof that log_G(hek_i) = log_c1(D_i)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DleqProof {
+    pub challenge: JubjubScalar, // e = H(G, c1, hek_i, D_i, r * G, r * c1)
+    pub response: JubjubScalar,  // z = r + e * hdk_i
+}
+
+/// Partial decryption D_i = hdk_i * c1 of custodian i with its proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialDecryption {
+    pub index: u64,
+    pub share: EdwardsAffine,
+    pub proof: DleqProof,
+}
+
+/// Why partial decryptions could not be combined into the block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ThresholdError {
+    /// c1 is not in Jubjub's prime-order subgroup
+    InvalidCiphertext,
+    /// Fewer than `threshold` distinct partial decryptions carried a valid proof
+    NotEnoughShares { valid: usize, threshold: usize, rejected: Vec<u64> },
+    /// The combined plaintext does not hash to the block ID
+    BlockIdMismatch,
+}
+
+impl fmt::Display for ThresholdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThresholdError::InvalidCiphertext => write!(f, "c1 is not in the prime-order subgroup"),
+            ThresholdError::NotEnoughShares { valid, threshold, rejected } => {
+                write!(f, "{} valid shares of the {} needed (rejected {:?})", valid, threshold, rejected)
+            }
+            ThresholdError::BlockIdMismatch => write!(f, "decrypted block does not match its block ID"),
+        }
+    }
+}
+
+impl std::error::Error for ThresholdError {}
+
+/// Deals a fresh hdk as `n` shares of which any `threshold` decrypt
+/// Returns the public threshold key, the shares and hdk itself, which the dealer must discard.
+pub fn generate_threshold_key<R: Rng>(rng: &mut R, threshold: usize, n: usize) -> (ThresholdKey, Vec<KeyShare>, JubjubScalar) {
+    assert!(threshold >= 1 && threshold <= n, "threshold must be between 1 and the number of custodians");
+    let g = EdwardsAffine::generator(); // Prime-order subgroup generator
+
+    // f(x) = hdk + a_1 x + ... + a_(t-1) x^(t-1)
+    let coefficients: Vec<JubjubScalar> = (0..threshold).map(|_| JubjubScalar::rand(rng)).collect();
+    let shares: Vec<KeyShare> = (1..=n as u64)
+        .map(|index| {
+            let x = JubjubScalar::from(index);
+            let share = coefficients.iter().rev().fold(JubjubScalar::zero(), |acc, a| acc * x + a);
+            KeyShare { index, share }
+        })
+        .collect();
+
+    let hdk = coefficients[0];
+    let key = ThresholdKey {
+        hek: (g * hdk).into_affine(),
+        verification_keys: shares.iter().map(|s| (g * s.share).into_affine()).collect(),
+        threshold,
+    };
+    (key, shares, hdk)
+}
+
+/// e = H(domain, G, c1, hek_i, D_i, A1, A2), reduced into Jubjub's scalar field
+fn dleq_challenge(points: [&EdwardsAffine; 6]) -> JubjubScalar {
+    let mut hasher = Sha256::new();
+    hasher.update(DLEQ_DOMAIN);
+    for point in points {
+        let mut bytes = Vec::new();
+        point.serialize_compressed(&mut bytes).unwrap();
+        hasher.update(bytes);
+    }
+    JubjubScalar::from_le_bytes_mod_order(&hasher.finalize())
+}
+
+/// Custodian i's share of the decryption of a ciphertext with first component c1
+pub fn partial_decrypt<R: Rng>(share: &KeyShare, c1: &EdwardsAffine, rng: &mut R) -> PartialDecryption {
+    let g = EdwardsAffine::generator();
+    let verification_key = (g * share.share).into_affine();
+    let d = (*c1 * share.share).into_affine(); // D_i = hdk_i * c1
+
+    let r = JubjubScalar::rand(rng); // Commitment randomness
+    let a1 = (g * r).into_affine();
+    let a2 = (*c1 * r).into_affine();
+    let challenge = dleq_challenge([&g, c1, &verification_key, &d, &a1, &a2]);
+    let response = r + challenge * share.share;
+
+    PartialDecryption { index: share.index, share: d, proof: DleqProof { challenge, response } }
+}
+
+/// Checks the DLEQ proof of a partial decryption against custodian i's verification key
+pub fn verify_partial_decryption(key: &ThresholdKey, c1: &EdwardsAffine, partial: &PartialDecryption) -> bool {
+    let Some(verification_key) = (partial.index as usize)
+        .checked_sub(1)
+        .and_then(|i| key.verification_keys.get(i))
+    else {
+        return false;
+    };
+    if !partial.share.is_in_correct_subgroup_assuming_on_curve() {
+        return false;
+    }
+
+    // A1 = z * G - e * hek_i and A2 = z * c1 - e * D_i
+    let g = EdwardsAffine::generator();
+    let DleqProof { challenge, response } = partial.proof;
+    let a1 = (g * response - *verification_key * challenge).into_affine();
+    let a2 = (*c1 * response - partial.share * challenge).into_affine();
+    dleq_challenge([&g, c1, verification_key, &partial.share, &a1, &a2]) == challenge
+}
+
+/// Lagrange coefficient at 0 of index `i` over the interpolation set `indices`
+fn lagrange_at_zero(i: u64, indices: &[u64]) -> JubjubScalar {
+    let x_i = JubjubScalar::from(i);
+    indices.iter().filter(|&&j| j != i).fold(JubjubScalar::ONE, |acc, &j| {
+        let x_j = JubjubScalar::from(j);
+        acc * x_j * (x_j - x_i).inverse().unwrap()
+    })
+}
+
+/// Combines partial decryptions of (c1, c2) into m and checks it against bid
+///
+/// Partial decryptions with an invalid proof or a repeated index are discarded, so one
+/// malicious custodian cannot corrupt the result as long as `threshold` honest ones answer.
+pub fn combine_partial_decryptions(
+    key: &ThresholdKey,
+    ct: &(EdwardsAffine, Fr),
+    partials: &[PartialDecryption],
+    bid: &[u8; 32],
+    hash: BlockIdHash,
+) -> Result<Fr, ThresholdError> {
+    let (c1, c2) = ct;
+    if !c1.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(ThresholdError::InvalidCiphertext);
+    }
+
+    let mut valid: Vec<&PartialDecryption> = Vec::new();
+    let mut rejected = Vec::new();
+    for partial in partials {
+        if valid.iter().any(|v| v.index == partial.index) {
+            continue;
+        }
+        if verify_partial_decryption(key, c1, partial) {
+            valid.push(partial);
+        } else {
+            rejected.push(partial.index);
+        }
+    }
+    if valid.len() < key.threshold {
+        return Err(ThresholdError::NotEnoughShares { valid: valid.len(), threshold: key.threshold, rejected });
+    }
+
+    // s = hdk * c1 = sum of lambda_i * D_i over any threshold valid shares
+    let chosen = &valid[..key.threshold];
+    let indices: Vec<u64> = chosen.iter().map(|p| p.index).collect();
+    let s = chosen
+        .iter()
+        .map(|p| p.share * lagrange_at_zero(p.index, &indices))
+        .sum::<ark_ed_on_bls12_381::EdwardsProjective>()
+        .into_affine();
+
+    // EC-ElGamal decryption: m = c2 - s.x
+    let message = *c2 - s.x;
+    if hash.block_id(&message) != *bid {
+        return Err(ThresholdError::BlockIdMismatch);
+    }
+    Ok(message)
+}
+
+/// Deals a t-of-n key and encrypts a random message under it
+/// Returns (key, shares, ciphertext, message, bid).
+pub fn setup_threshold_ec_elgamal<R: Rng>(
+    rng: &mut R,
+    threshold: usize,
+    n: usize,
+) -> (ThresholdKey, Vec<KeyShare>, (EdwardsAffine, Fr), Fr, [u8; 32]) {
+    let (key, shares, _hdk) = generate_threshold_key(rng, threshold, n);
+    let message = Fr::rand(rng); // Random message to encrypt
+    let y = JubjubScalar::rand(rng); // Random nonce
+    let ct = encrypt_ec_elgamal(&key.hek, &message, &y);
+    let bid = BlockIdHash::Sha256.block_id(&message);
+    (key, shares, ct, message, bid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_any_threshold_subset_decrypts() {
+        let mut rng = test_rng();
+        let (key, shares, ct, message, bid) = setup_threshold_ec_elgamal(&mut rng, 3, 5);
+
+        for subset in [[0, 1, 2], [0, 2, 4], [4, 3, 1]] {
+            let partials: Vec<_> = subset.iter().map(|&i| partial_decrypt(&shares[i], &ct.0, &mut rng)).collect();
+            assert!(partials.iter().all(|p| verify_partial_decryption(&key, &ct.0, p)));
+            assert_eq!(combine_partial_decryptions(&key, &ct, &partials, &bid, BlockIdHash::Sha256), Ok(message));
+        }
+    }
+
+    #[test]
+    fn test_shares_reconstruct_hdk() {
+        let mut rng = test_rng();
+        let (key, shares, hdk) = generate_threshold_key(&mut rng, 2, 3);
+        let indices = [1u64, 3];
+        let reconstructed = lagrange_at_zero(1, &indices) * shares[0].share + lagrange_at_zero(3, &indices) * shares[2].share;
+        assert_eq!(reconstructed, hdk);
+        assert_eq!(key.hek, (EdwardsAffine::generator() * hdk).into_affine());
+    }
+
+    #[test]
+    fn test_too_few_shares() {
+        let mut rng = test_rng();
+        let (key, shares, ct, _message, bid) = setup_threshold_ec_elgamal(&mut rng, 3, 5);
+
+        // The same custodian twice still counts once
+        let partial = partial_decrypt(&shares[0], &ct.0, &mut rng);
+        let partials = vec![partial, partial, partial_decrypt(&shares[1], &ct.0, &mut rng)];
+        assert_eq!(
+            combine_partial_decryptions(&key, &ct, &partials, &bid, BlockIdHash::Sha256),
+            Err(ThresholdError::NotEnoughShares { valid: 2, threshold: 3, rejected: vec![] })
+        );
+    }
+
+    #[test]
+    fn test_malicious_share_is_detected_and_skipped() {
+        let mut rng = test_rng();
+        let (key, shares, ct, message, bid) = setup_threshold_ec_elgamal(&mut rng, 2, 4);
+
+        // Custodian 1 returns a wrong point, re-using the proof of its honest share
+        let honest = partial_decrypt(&shares[0], &ct.0, &mut rng);
+        let wrong_point = (honest.share + EdwardsAffine::generator()).into_affine();
+        let malicious = PartialDecryption { share: wrong_point, ..honest };
+        assert!(!verify_partial_decryption(&key, &ct.0, &malicious));
+
+        // Custodian 2 decrypts with a key it does not hold, proving against that key honestly
+        let forged = partial_decrypt(&KeyShare { index: 2, share: JubjubScalar::rand(&mut rng) }, &ct.0, &mut rng);
+        assert!(!verify_partial_decryption(&key, &ct.0, &forged));
+
+        // Without the proof check the wrong point would have corrupted the plaintext
+        let s = (malicious.share * lagrange_at_zero(1, &[1, 3])
+            + partial_decrypt(&shares[2], &ct.0, &mut rng).share * lagrange_at_zero(3, &[1, 3]))
+        .into_affine();
+        assert_ne!(ct.1 - s.x, message);
+
+        // Both are discarded; the result comes from the honest custodians
+        let partials = vec![
+            malicious,
+            forged,
+            partial_decrypt(&shares[2], &ct.0, &mut rng),
+            partial_decrypt(&shares[3], &ct.0, &mut rng),
+        ];
+        assert_eq!(combine_partial_decryptions(&key, &ct, &partials, &bid, BlockIdHash::Sha256), Ok(message));
+
+        // With only one honest custodian left, the malicious ones are reported
+        assert_eq!(
+            combine_partial_decryptions(&key, &ct, &partials[..3], &bid, BlockIdHash::Sha256),
+            Err(ThresholdError::NotEnoughShares { valid: 1, threshold: 2, rejected: vec![1, 2] })
+        );
+    }
+
+    #[test]
+    fn test_combined_block_must_match_bid() {
+        let mut rng = test_rng();
+        let (key, shares, ct, _message, mut bid) = setup_threshold_ec_elgamal(&mut rng, 2, 3);
+        let partials: Vec<_> = shares[..2].iter().map(|s| partial_decrypt(s, &ct.0, &mut rng)).collect();
+
+        bid[0] ^= 1;
+        assert_eq!(
+            combine_partial_decryptions(&key, &ct, &partials, &bid, BlockIdHash::Sha256),
+            Err(ThresholdError::BlockIdMismatch)
+        );
+    }
+}
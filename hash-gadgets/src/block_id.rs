@@ -0,0 +1,171 @@
+use ark_crypto_primitives::crh::{
+    sha256::constraints::{Sha256Gadget, UnitVar},
+    CRHSchemeGadget,
+};
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    convert::{ToBitsGadget, ToBytesGadget},
+    prelude::Boolean,
+    uint8::UInt8,
+};
+use ark_relations::r1cs::SynthesisError;
+use ark_std::vec::Vec;
+use sha2::Digest;
+
+use crate::keccak::{keccak256, Keccak256Gadget};
+use crate::poseidon::{poseidon_hash, PoseidonGadget};
+
+/// Hash function used to derive a block ID from a field element encoded as 32 bytes
+///
+/// Every circuit lays the hashed value out as 32 little-endian bytes. SHA256 hashes those
+/// bytes directly, which is the original encoding. Keccak-256 hashes them big-endian, so the
+/// block ID equals Solidity's `keccak256(abi.encodePacked(uint256(x)))`. Poseidon packs the
+/// bytes back into elements of the proving field, so it is not compatible with either and
+/// its block IDs depend on the field the circuit runs over.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockIdHash {
+    #[default]
+    Sha256,
+    Keccak256,
+    Poseidon,
+}
+
+impl BlockIdHash {
+    /// Native block ID of a field element, for circuits proven over the element's own field
+    pub fn block_id<P: PrimeField + Absorb>(&self, value: &P) -> [u8; 32] {
+        self.block_id_in::<P, P>(value)
+    }
+
+    /// Native block ID of a field element hashed inside a circuit over `F`
+    /// Only Poseidon depends on `F`; the byte-oriented hashes ignore it.
+    pub fn block_id_in<F: PrimeField + Absorb, P: PrimeField>(&self, value: &P) -> [u8; 32] {
+        let mut bytes = value.into_bigint().to_bytes_le();
+        bytes.resize(32, 0);
+        self.hash_le_bytes::<F>(&bytes)
+    }
+
+    /// Native counterpart of `block_id_gadget` over the same 32 little-endian bytes
+    pub fn hash_le_bytes<F: PrimeField + Absorb>(&self, bytes_le: &[u8]) -> [u8; 32] {
+        assert_eq!(bytes_le.len(), 32, "block IDs hash a 32-byte encoding");
+        match self {
+            BlockIdHash::Sha256 => sha2::Sha256::digest(bytes_le).into(),
+            BlockIdHash::Keccak256 => {
+                let bytes_be: Vec<u8> = bytes_le.iter().rev().copied().collect();
+                keccak256(&bytes_be)
+            }
+            BlockIdHash::Poseidon => {
+                let elements: Vec<F> = bytes_le
+                    .chunks(Self::bytes_per_element::<F>())
+                    .map(F::from_le_bytes_mod_order)
+                    .collect();
+                let mut digest = poseidon_hash(&elements).into_bigint().to_bytes_le();
+                digest.resize(32, 0);
+                digest[..32].try_into().unwrap()
+            }
+        }
+    }
+
+    /// Whole bytes that always fit below the modulus of `F`
+    fn bytes_per_element<F: PrimeField>() -> usize {
+        (F::MODULUS_BIT_SIZE as usize - 1) / 8
+    }
+
+    /// In-circuit block ID over the 32 little-endian bytes of a value
+    pub fn block_id_gadget<F: PrimeField>(
+        &self,
+        bytes_le: &[UInt8<F>],
+    ) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        assert_eq!(bytes_le.len(), 32, "block IDs hash a 32-byte encoding");
+        match self {
+            BlockIdHash::Sha256 => {
+                let params_var = UnitVar::default();
+                Ok(Sha256Gadget::evaluate(&params_var, bytes_le)?.0)
+            }
+            BlockIdHash::Keccak256 => {
+                let bytes_be: Vec<UInt8<F>> = bytes_le.iter().rev().cloned().collect();
+                Keccak256Gadget::digest(&bytes_be)
+            }
+            BlockIdHash::Poseidon => {
+                // Repacking bytes that are already bit-decomposed is free (linear combinations)
+                let elements = bytes_le
+                    .chunks(Self::bytes_per_element::<F>())
+                    .map(|chunk| Boolean::le_bits_to_fp(&chunk.to_bits_le()?))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let digest = PoseidonGadget::hash(&elements)?;
+                let mut digest_bytes = digest.to_bytes_le()?;
+                digest_bytes.truncate(32);
+                Ok(digest_bytes)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    /// Computes the block ID of `value` in-circuit and returns it with the native one
+    fn gadget_and_native(hash: BlockIdHash, value: Fr) -> (Vec<u8>, [u8; 32]) {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let mut bytes = value.into_bigint().to_bytes_le();
+        bytes.resize(32, 0);
+        let bytes_var = UInt8::<Fr>::new_witness_vec(cs.clone(), &bytes).unwrap();
+        let digest = hash.block_id_gadget(&bytes_var).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        (digest.iter().map(|b| b.value().unwrap()).collect(), hash.block_id(&value))
+    }
+
+    #[test]
+    fn test_keccak_block_ids_match_solidity() {
+        // keccak256(abi.encodePacked(uint256(x))) as evaluated by the EVM
+        let vectors = [
+            (0u64, "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563"),
+            (1u64, "b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6"),
+        ];
+        for (x, expected) in vectors {
+            let (gadget, native) = gadget_and_native(BlockIdHash::Keccak256, Fr::from(x));
+            assert_eq!(hex::encode(native), expected, "native keccak of uint256({})", x);
+            assert_eq!(hex::encode(gadget), expected, "circuit keccak of uint256({})", x);
+        }
+    }
+
+    #[test]
+    fn test_record_id_of_zero_block_ids_matches_solidity() {
+        // keccak256(abi.encodePacked([bytes32(0), bytes32(0)]))
+        let expected = "ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5";
+        assert_eq!(hex::encode(keccak256(&[0u8; 64])), expected);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let packed = UInt8::<Fr>::new_witness_vec(cs.clone(), &[0u8; 64]).unwrap();
+        let digest = Keccak256Gadget::digest(&packed).unwrap();
+        let digest: Vec<u8> = digest.iter().map(|b| b.value().unwrap()).collect();
+        assert_eq!(hex::encode(digest), expected);
+    }
+
+    #[test]
+    fn test_poseidon_block_id_gadget_matches_native() {
+        for value in [Fr::from(0u64), Fr::from(7u64), -Fr::from(1u64)] {
+            let (gadget, native) = gadget_and_native(BlockIdHash::Poseidon, value);
+            assert_eq!(gadget, native.to_vec());
+        }
+        assert_ne!(
+            BlockIdHash::Poseidon.block_id(&Fr::from(1u64)),
+            BlockIdHash::Poseidon.block_id(&Fr::from(2u64))
+        );
+    }
+
+    #[test]
+    fn test_sha256_block_ids_keep_little_endian_encoding() {
+        let value = Fr::from(0x0102030405u64);
+        let (gadget, native) = gadget_and_native(BlockIdHash::Sha256, value);
+        let mut bytes = value.into_bigint().to_bytes_le();
+        bytes.resize(32, 0);
+        let expected: [u8; 32] = sha2::Sha256::digest(&bytes).into();
+        assert_eq!(native, expected);
+        assert_eq!(gadget, expected.to_vec());
+    }
+}
@@ -0,0 +1,149 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    convert::ToBytesGadget,
+    uint64::UInt64,
+    uint8::UInt8,
+};
+use ark_relations::r1cs::SynthesisError;
+use ark_std::vec::Vec;
+use sha3::{Digest, Keccak256};
+
+/// Keccak-256 rate in bytes (1600-bit state minus 512-bit capacity)
+pub const KECCAK256_RATE: usize = 136;
+
+/// Round constants for the iota step
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// Rotation offsets for the rho step, indexed as [x][y]
+const ROTATION_OFFSETS: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+/// Native Keccak-256, identical to Solidity's `keccak256`
+pub fn keccak256(input: &[u8]) -> [u8; 32] {
+    Keccak256::digest(input).into()
+}
+
+/// R1CS gadget for Keccak-256 (the pre-standard padding used by Ethereum, not SHA3-256)
+///
+/// The input length is fixed at synthesis time, so padding is made of constant bytes and
+/// only the permutation itself costs constraints (roughly 150k per 136-byte block).
+pub struct Keccak256Gadget;
+
+impl Keccak256Gadget {
+    /// Hashes `input` and returns the 32-byte digest in the same order as `keccak256`
+    pub fn digest<F: PrimeField>(input: &[UInt8<F>]) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        // pad10*1 with the Keccak domain byte 0x01; always adds between 1 and 136 bytes
+        let pad_len = KECCAK256_RATE - input.len() % KECCAK256_RATE;
+        let mut padding = vec![0u8; pad_len];
+        padding[0] |= 0x01;
+        padding[pad_len - 1] |= 0x80;
+
+        let mut padded = input.to_vec();
+        padded.extend(padding.into_iter().map(UInt8::constant));
+
+        // Absorb
+        let mut state: Vec<UInt64<F>> = (0..25).map(|_| UInt64::constant(0)).collect();
+        for block in padded.chunks(KECCAK256_RATE) {
+            for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+                *lane ^= UInt64::from_bytes_le(bytes)?;
+            }
+            Self::permute(&mut state)?;
+        }
+
+        // Squeeze the first 256 bits
+        let mut output = Vec::with_capacity(32);
+        for lane in state.iter().take(4) {
+            output.extend(lane.to_bytes_le()?);
+        }
+        Ok(output)
+    }
+
+    /// Keccak-f[1600] permutation over 25 little-endian lanes, lane (x, y) at index x + 5y
+    pub fn permute<F: PrimeField>(state: &mut [UInt64<F>]) -> Result<(), SynthesisError> {
+        assert_eq!(state.len(), 25);
+
+        for round_constant in ROUND_CONSTANTS.iter() {
+            // theta
+            let columns: Vec<UInt64<F>> = (0..5)
+                .map(|x| {
+                    &state[x] ^ &state[x + 5] ^ &state[x + 10] ^ &state[x + 15] ^ &state[x + 20]
+                })
+                .collect();
+            for x in 0..5 {
+                let d = &columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+                for y in 0..5 {
+                    state[x + 5 * y] ^= &d;
+                }
+            }
+
+            // rho and pi
+            let mut rotated = state.to_vec();
+            for x in 0..5 {
+                for y in 0..5 {
+                    rotated[y + 5 * ((2 * x + 3 * y) % 5)] =
+                        state[x + 5 * y].rotate_left(ROTATION_OFFSETS[x][y]);
+                }
+            }
+
+            // chi
+            for y in 0..5 {
+                for x in 0..5 {
+                    let not_next = !&rotated[(x + 1) % 5 + 5 * y];
+                    state[x + 5 * y] =
+                        &rotated[x + 5 * y] ^ (not_next & &rotated[(x + 2) % 5 + 5 * y]);
+                }
+            }
+
+            // iota
+            state[0] ^= *round_constant;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn check_against_native(input: &[u8]) {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let input_var = UInt8::<Fr>::new_witness_vec(cs.clone(), input).unwrap();
+        let digest = Keccak256Gadget::digest(&input_var).unwrap();
+        let digest: Vec<u8> = digest.iter().map(|b| b.value().unwrap()).collect();
+
+        assert_eq!(digest, keccak256(input), "length {}", input.len());
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_keccak_known_vector() {
+        // keccak256("") as returned by Solidity
+        let expected = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+        assert_eq!(hex::encode(keccak256(b"")), expected);
+        check_against_native(b"");
+    }
+
+    #[test]
+    fn test_keccak_gadget_matches_native_across_block_boundaries() {
+        for len in [1usize, 32, 135, 136, 137, 272] {
+            let input: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            check_against_native(&input);
+        }
+    }
+}
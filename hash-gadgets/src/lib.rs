@@ -0,0 +1,5 @@
+pub mod block_id;
+pub mod keccak;
+pub mod poseidon;
+
+pub use block_id::BlockIdHash;
@@ -0,0 +1,128 @@
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge},
+    Absorb, CryptographicSponge,
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{fields::fp::FpVar, R1CSVar};
+use ark_relations::r1cs::SynthesisError;
+
+/// Sponge rate (field elements absorbed per permutation); the state width is rate + 1
+pub const POSEIDON_RATE: usize = 2;
+/// Number of full S-box rounds
+pub const POSEIDON_FULL_ROUNDS: usize = 8;
+
+/// Candidate S-box exponents, smallest first
+const ALPHA_CANDIDATES: [u64; 6] = [3, 5, 7, 11, 13, 17];
+
+/// Smallest exponent alpha with gcd(alpha, p - 1) = 1, so that x^alpha is a permutation of F
+pub fn poseidon_alpha<F: PrimeField>() -> u64 {
+    ALPHA_CANDIDATES
+        .into_iter()
+        .find(|alpha| {
+            // alpha divides p - 1 exactly when p mod alpha = 1 (alpha is prime)
+            let p_mod_alpha = F::MODULUS
+                .as_ref()
+                .iter()
+                .rev()
+                .fold(0u128, |acc, limb| ((acc << 64) + *limb as u128) % *alpha as u128);
+            p_mod_alpha != 1
+        })
+        .expect("no supported Poseidon S-box exponent for this field")
+}
+
+/// Poseidon parameters for width-3 instances at the 128-bit security level
+///
+/// Round numbers follow the Poseidon paper for t = 3: 84 partial rounds with x^3 and
+/// 57 with x^5 or any larger exponent. Round constants and the MDS matrix come from the
+/// reference Grain LFSR, so the same field always yields the same parameters.
+pub fn poseidon_config<F: PrimeField>() -> PoseidonConfig<F> {
+    let alpha = poseidon_alpha::<F>();
+    let partial_rounds = if alpha == 3 { 84 } else { 57 };
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        POSEIDON_RATE,
+        POSEIDON_FULL_ROUNDS as u64,
+        partial_rounds,
+        0,
+    );
+    PoseidonConfig::new(POSEIDON_FULL_ROUNDS, partial_rounds as usize, alpha, mds, ark, POSEIDON_RATE, 1)
+}
+
+/// Native Poseidon sponge hash of a sequence of field elements to one field element
+pub fn poseidon_hash<F: PrimeField + Absorb>(inputs: &[F]) -> F {
+    poseidon_squeeze(inputs, 1)[0]
+}
+
+/// Native Poseidon sponge that absorbs `inputs` and squeezes `count` field elements
+/// Used as a keystream when the inputs contain a secret key
+pub fn poseidon_squeeze<F: PrimeField + Absorb>(inputs: &[F], count: usize) -> Vec<F> {
+    let mut sponge = PoseidonSponge::new(&poseidon_config::<F>());
+    sponge.absorb(&inputs);
+    sponge.squeeze_field_elements::<F>(count)
+}
+
+/// R1CS gadget for `poseidon_hash`
+///
+/// Every S-box is a small power in the proving field itself, so one permutation costs a
+/// few hundred constraints instead of the tens of thousands of a bit-oriented hash.
+pub struct PoseidonGadget;
+
+impl PoseidonGadget {
+    /// Hashes `inputs` and returns the single squeezed field element
+    pub fn hash<F: PrimeField>(inputs: &[FpVar<F>]) -> Result<FpVar<F>, SynthesisError> {
+        let mut sponge = PoseidonSpongeVar::new(inputs.cs(), &poseidon_config::<F>());
+        sponge.absorb(&inputs)?;
+        Ok(sponge.squeeze_field_elements(1)?.remove(0))
+    }
+
+    /// In-circuit counterpart of `poseidon_squeeze`
+    pub fn squeeze<F: PrimeField>(inputs: &[FpVar<F>], count: usize) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut sponge = PoseidonSpongeVar::new(inputs.cs(), &poseidon_config::<F>());
+        sponge.absorb(&inputs)?;
+        sponge.squeeze_field_elements(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_r1cs_std::alloc::AllocVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_poseidon_alpha_is_a_permutation_exponent() {
+        // BLS12-381 Fr has 3 | r - 1 but not 5, the exponent used by Filecoin and Zcash
+        assert_eq!(poseidon_alpha::<Fr>(), 5);
+    }
+
+    #[test]
+    fn test_poseidon_gadget_matches_native() {
+        for len in [1u64, 2, 3, 5] {
+            let inputs: Vec<Fr> = (0..len).map(|i| Fr::from(i * 7 + 3)).collect();
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let input_vars = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs.clone())).unwrap();
+            let digest = PoseidonGadget::hash(&input_vars).unwrap();
+
+            assert_eq!(digest.value().unwrap(), poseidon_hash(&inputs), "length {}", len);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_poseidon_squeeze_gadget_matches_native() {
+        let key = Fr::from(42u64);
+        for count in [1usize, 2, 5] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let key_var = FpVar::<Fr>::new_witness(cs.clone(), || Ok(key)).unwrap();
+            let stream = PoseidonGadget::squeeze(&[key_var], count).unwrap();
+
+            let values: Vec<Fr> = stream.iter().map(|x| x.value().unwrap()).collect();
+            assert_eq!(values, poseidon_squeeze(&[key], count), "count {}", count);
+            assert!(cs.is_satisfied().unwrap());
+        }
+        assert_eq!(poseidon_squeeze(&[key], 1)[0], poseidon_hash(&[key]));
+    }
+}
@@ -0,0 +1,168 @@
+//! Groth16 key and proof files shared by the proof binaries
+//!
+//! Every circuit gets its own directory, by default `keys/<circuit>` under the working
+//! directory (or under `$KEYS_DIR` when set), holding:
+//!
+//! | File                | Contents                                  | Written by |
+//! |---------------------|-------------------------------------------|------------|
+//! | `proving_key.bin`   | `ProvingKey<E>`                           | `setup`    |
+//! | `verifying_key.bin` | `VerifyingKey<E>`                         | `setup`    |
+//! | `proof.bin`         | `Proof<E>` of the last proven block       | `prove`    |
+//! | `public_inputs.bin` | `Vec<E::ScalarField>` for that proof      | `prove`    |
+//! | `witness.bin`       | Inputs of the block to prove, see below   | the prover |
+//!
+//! `prove` reads the block from `witness.bin`: the circuit's public and private inputs as
+//! one tuple, nested where a circuit has more than five. The tuples of the
+//! elgamal-proof variants, with `F` the scalar field of the proving curve, are:
+//!
+//! | Variant        | Tuple                                                            |
+//! |----------------|------------------------------------------------------------------|
+//! | `fr`           | `(ct: [F; 2], hek: F, bid: [u8; 32], hdk: F)`                    |
+//! | `jubjub`       | `(ct: (EdwardsAffine, Fr), hek: EdwardsAffine, bid, hdk: JubjubScalar)` |
+//! | `encryption`   | `(ct, hek, bid, y, message)`                                     |
+//! | `reencryption` | `((ct, hek), (buyer_ct, buyer_pk), bid, (hdk, y))`               |
+//! | `derived`      | `((ct, hek), bid, (master_commitment, path), master)`            |
+//! | `dispute`      | `(ct, hdk, bid)`                                                 |
+//! | `hybrid`       | `((kem: [F; 2], body: Vec<F>, len: usize), hek, bid, hdk)`       |
+//!
+//! The `witness` command writes a random block in this format. abe-proof and pairing-proof
+//! take BLS12-377 values and their `witness` command writes the sample block:
+//!
+//! | Binary          | Tuple                                                                      |
+//! |-----------------|----------------------------------------------------------------------------|
+//! | `abe-proof`     | `((s, k, λ, t, w), (ct0: Fq12, ct1: Fq12, ct2: G1Affine, ct3: Fr, ct4: G1Affine), (attr_hash_point, pk0, pk1, bid))` |
+//! | `pairing-proof` | `(c_star: G1Affine, β: Fr, γ: Fr, γ_hash: [u8; 32])`                       |
+//!
+//! All files use `ark-serialize`'s compressed encoding. The proving key is read back
+//! without subgroup checks since it never leaves the prover; everything a verifier reads
+//! is fully validated.
+
+use ark_ec::pairing::Pairing;
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// Overrides the root directory of all key directories
+pub const KEYS_DIR_ENV: &str = "KEYS_DIR";
+
+pub const PROVING_KEY_FILE: &str = "proving_key.bin";
+pub const VERIFYING_KEY_FILE: &str = "verifying_key.bin";
+pub const PROOF_FILE: &str = "proof.bin";
+pub const PUBLIC_INPUTS_FILE: &str = "public_inputs.bin";
+pub const WITNESS_FILE: &str = "witness.bin";
+
+/// Directory holding the files of circuit `name`
+pub fn key_dir(name: &str) -> PathBuf {
+    let root = std::env::var_os(KEYS_DIR_ENV).map_or_else(|| PathBuf::from("keys"), PathBuf::from);
+    root.join(name)
+}
+
+fn write_file<T: CanonicalSerialize>(path: &Path, value: &T) -> Result<(), SerializationError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    value.serialize_compressed(&mut writer)
+}
+
+fn read_file<T: CanonicalDeserialize>(path: &Path) -> Result<T, SerializationError> {
+    T::deserialize_compressed(BufReader::new(File::open(path)?))
+}
+
+/// Writes both keys produced by `circuit_specific_setup`, creating `dir` if needed
+pub fn save_keys<E: Pairing>(dir: &Path, pk: &ProvingKey<E>, vk: &VerifyingKey<E>) -> Result<(), SerializationError> {
+    fs::create_dir_all(dir)?;
+    write_file(&dir.join(PROVING_KEY_FILE), pk)?;
+    write_file(&dir.join(VERIFYING_KEY_FILE), vk)
+}
+
+/// Reads the proving key written by `save_keys`
+pub fn load_proving_key<E: Pairing>(dir: &Path) -> Result<ProvingKey<E>, SerializationError> {
+    // Skipping point validation keeps loading fast for keys with millions of points
+    let reader = BufReader::new(File::open(dir.join(PROVING_KEY_FILE))?);
+    ProvingKey::deserialize_compressed_unchecked(reader)
+}
+
+/// Reads the verifying key written by `save_keys`
+pub fn load_verifying_key<E: Pairing>(dir: &Path) -> Result<VerifyingKey<E>, SerializationError> {
+    read_file(&dir.join(VERIFYING_KEY_FILE))
+}
+
+/// Writes a proof together with the public inputs it should verify against
+pub fn save_proof<E: Pairing>(dir: &Path, proof: &Proof<E>, public_inputs: &[E::ScalarField]) -> Result<(), SerializationError> {
+    fs::create_dir_all(dir)?;
+    write_file(&dir.join(PROOF_FILE), proof)?;
+    write_file(&dir.join(PUBLIC_INPUTS_FILE), &public_inputs.to_vec())
+}
+
+/// Reads the proof and public inputs written by `save_proof`
+pub fn load_proof<E: Pairing>(dir: &Path) -> Result<(Proof<E>, Vec<E::ScalarField>), SerializationError> {
+    Ok((read_file(&dir.join(PROOF_FILE))?, read_file(&dir.join(PUBLIC_INPUTS_FILE))?))
+}
+
+/// Writes the inputs of a block to prove, creating `dir` if needed
+pub fn save_witness<W: CanonicalSerialize>(dir: &Path, witness: &W) -> Result<(), SerializationError> {
+    fs::create_dir_all(dir)?;
+    write_file(&dir.join(WITNESS_FILE), witness)
+}
+
+/// Reads the inputs of a block to prove, as written by `save_witness`
+pub fn load_witness<W: CanonicalDeserialize>(dir: &Path) -> Result<W, SerializationError> {
+    read_file(&dir.join(WITNESS_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_groth16::Groth16;
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+    use ark_snark::SNARK;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    /// Proves knowledge of x with x * x = y for public y
+    #[derive(Clone)]
+    struct SquareCircuit {
+        x: Fr,
+    }
+
+    impl ConstraintSynthesizer<Fr> for SquareCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let y = cs.new_input_variable(|| Ok(self.x * self.x))?;
+            let x = cs.new_witness_variable(|| Ok(self.x))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)
+        }
+    }
+
+    #[test]
+    fn test_keys_and_proof_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let dir = std::env::temp_dir().join(format!("key-store-{}", std::process::id()));
+        let circuit = SquareCircuit { x: Fr::from(7u64) };
+
+        let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        save_keys(&dir, &pk, &vk).unwrap();
+
+        save_witness(&dir, &([Fr::from(7u64)], 49u64)).unwrap();
+
+        let pk = load_proving_key::<Bls12_381>(&dir).unwrap();
+        let ([x], y): ([Fr; 1], u64) = load_witness(&dir).unwrap();
+        assert_eq!((x, y), (circuit.x, 49));
+        let proof = Groth16::<Bls12_381>::prove(&pk, SquareCircuit { x }, &mut rng).unwrap();
+        save_proof(&dir, &proof, &[Fr::from(49u64)]).unwrap();
+
+        let vk_loaded = load_verifying_key::<Bls12_381>(&dir).unwrap();
+        let (proof_loaded, public_inputs) = load_proof::<Bls12_381>(&dir).unwrap();
+        assert_eq!(vk_loaded, vk);
+        assert_eq!(proof_loaded, proof);
+        assert!(Groth16::<Bls12_381>::verify(&vk_loaded, &public_inputs, &proof_loaded).unwrap());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_keys_are_an_error() {
+        let dir = std::env::temp_dir().join("key-store-missing");
+        assert!(load_verifying_key::<Bls12_381>(&dir).is_err());
+    }
+}
@@ -16,10 +16,8 @@ use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisE
 use ark_r1cs_std::convert::ToBitsGadget;
 use ark_r1cs_std::fields::FieldVar;
 use ark_r1cs_std::uint8::UInt8;
-use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
-use ark_crypto_primitives::crh::CRHSchemeGadget;
 use ark_r1cs_std::prelude::Boolean;
-use ark_crypto_primitives::crh::sha256::constraints::UnitVar;
+use hash_gadgets::BlockIdHash;
 
 #[derive(Clone)]
 pub struct PairingCircuit {
@@ -27,6 +25,7 @@ pub struct PairingCircuit {
     pub beta: Fr, // Private witness β
     pub gamma: Fr, // Private witness γ
     pub gamma_hash: [u8; 32], // Hash of γ as public input
+    pub hash: BlockIdHash, // Hash function deriving gamma_hash from γ
 }
 
 impl ConstraintSynthesizer<Fq> for PairingCircuit {
@@ -42,7 +41,7 @@ impl ConstraintSynthesizer<Fq> for PairingCircuit {
         )?;
         
         // === Allocate gamma hash as public input ===
-        let gamma_hash_var = UInt8::<Fq>::new_input_vec(cs.clone(), &self.gamma_hash.to_vec())?;
+        let gamma_hash_var = UInt8::<Fq>::new_input_vec(cs.clone(), &self.gamma_hash)?;
         
         // === Constants (reuse prepared versions when possible) ===
         let g1_gen = G1Var::<BLSConfig>::new_constant(
@@ -75,15 +74,14 @@ impl ConstraintSynthesizer<Fq> for PairingCircuit {
         // Convert to bytes more efficiently
         let gamma_bytes: Vec<UInt8<Fq>> = gamma_bits_padded
             .chunks_exact(8)
-            .map(|chunk| UInt8::from_bits_le(chunk))
+            .map(UInt8::from_bits_le)
             .collect();
 
-        // Compute SHA256(gamma_bytes)
-        let params_var = UnitVar::default();
-        let sha_hash = Sha256Gadget::evaluate(&params_var, &gamma_bytes)?;
+        // Compute H(gamma_bytes); with Keccak-256 this is the contract's keccak256(abi.encodePacked(gamma))
+        let gamma_digest = self.hash.block_id_gadget(&gamma_bytes)?;
         
-        // Enforce equality: computed SHA256(gamma) == public input hash
-        sha_hash.0.enforce_equal(&gamma_hash_var)?;
+        // Enforce equality: computed H(gamma) == public input hash
+        gamma_digest.enforce_equal(&gamma_hash_var)?;
         
         // === Compute g2^γ (reuse gamma_bits from hash computation) ===
         let g2_gamma_var = g2_gen.scalar_mul_le(gamma_bits.iter())?;
@@ -105,4 +103,66 @@ impl ConstraintSynthesizer<Fq> for PairingCircuit {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute_gamma_hash, sample_circuit};
+    use ark_ec::CurveGroup;
+    use ark_relations::r1cs::ConstraintSystem;
+    use proptest::prelude::*;
+
+    fn is_satisfied(circuit: PairingCircuit) -> bool {
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_sample_circuit_is_satisfied() {
+        assert!(is_satisfied(sample_circuit(BlockIdHash::Sha256)));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        /// C* moved by g1^delta stays in the prime-order subgroup but breaks the pairing equation
+        #[test]
+        fn prop_rejects_tampered_c_star(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.c_star_affine = (G1Affine::generator() * Fr::from(delta) + circuit.c_star_affine).into_affine();
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_gamma_hash(index in 0..32usize, flip in 1..=u8::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.gamma_hash[index] ^= flip;
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_gamma(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.gamma += Fr::from(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        /// A γ that matches its hash must still satisfy the pairing equation
+        #[test]
+        fn prop_rejects_tampered_gamma_with_matching_hash(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.gamma += Fr::from(delta);
+            circuit.gamma_hash = compute_gamma_hash(circuit.gamma, circuit.hash);
+            prop_assert!(!is_satisfied(circuit));
+        }
+
+        #[test]
+        fn prop_rejects_tampered_beta(delta in 1..u64::MAX) {
+            let mut circuit = sample_circuit(BlockIdHash::Sha256);
+            circuit.beta += Fr::from(delta);
+            prop_assert!(!is_satisfied(circuit));
+        }
+    }
+}
@@ -0,0 +1,39 @@
+pub mod circuit;
+
+use ark_bls12_377::{Fq, Fr, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::Field;
+use hash_gadgets::BlockIdHash;
+
+use crate::circuit::PairingCircuit;
+
+fn compute_gamma_hash(gamma: Fr, hash: BlockIdHash) -> [u8; 32] {
+    // Little-endian bytes of γ for SHA256, big-endian (abi.encodePacked) for Keccak-256;
+    // Poseidon runs over the circuit field Fq
+    hash.block_id_in::<Fq, _>(&gamma)
+}
+
+/// Builds the demo circuit proving pairing(C*, g2^γ) = pairing(g1, g2)^β for fixed β and γ
+pub fn sample_circuit(hash: BlockIdHash) -> PairingCircuit {
+    // === Witness values ===
+    let beta = Fr::from(3u8);
+    let gamma = Fr::from(7u8);
+    let gamma_inv = gamma.inverse().unwrap();
+    let beta_over_gamma = beta * gamma_inv;
+
+    // === Create public group element ===
+    let g1_gen = G1Affine::generator();
+    let c_star_affine = (g1_gen * beta_over_gamma).into_affine();
+
+    // === Compute hash ===
+    let gamma_hash = compute_gamma_hash(gamma, hash);
+
+    // === Create circuit ===
+    PairingCircuit {
+        c_star_affine,
+        beta,
+        gamma,
+        gamma_hash,
+        hash,
+    }
+}
@@ -1,27 +1,41 @@
-use ark_bls12_377::{Fr, G1Affine, Fq};
+use ark_bls12_377::{Fq, Fr, G1Affine};
 use ark_bw6_761::{BW6_761, Fr as BW6Fr};
-use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::{Field, ToConstraintField, PrimeField};
-use ark_groth16::{Groth16, Proof};
+use ark_ff::PrimeField;
+use ark_groth16::Groth16;
 use ark_relations::r1cs::ConstraintSystem;
 use ark_snark::SNARK;
 use ark_groth16::r1cs_to_qap::LibsnarkReduction;
-use ark_std::rand::thread_rng;
-use sha2::{Sha256, Digest};
-use circuit::PairingCircuit;
+use ark_std::rand::{rngs::StdRng, thread_rng, SeedableRng};
+use ark_serialize::CanonicalSerialize;
+use bench_report::{BenchmarkOptions, BenchmarkReport, Iteration};
+use pairing_proof::{circuit::PairingCircuit, sample_circuit};
 use std::time::Instant;
 use ark_relations::r1cs::ConstraintSynthesizer;
 use ark_ff::BigInteger;
-use ark_poly::evaluations::multivariate::multilinear::MultilinearExtension;
- use ark_std::UniformRand;
-mod circuit;
-
-fn compute_gamma_hash(gamma: Fr) -> [u8; 32] {
-    let mut gamma_bytes = gamma.into_bigint().to_bytes_le();
-    gamma_bytes.resize(32, 0);
-    let mut hasher = Sha256::new();
-    hasher.update(&gamma_bytes);
-    hasher.finalize().into()
+use hash_gadgets::BlockIdHash;
+use key_store::{
+    key_dir, load_proof, load_proving_key, load_verifying_key, load_witness, save_keys, save_proof, save_witness,
+    PROOF_FILE, PROVING_KEY_FILE, PUBLIC_INPUTS_FILE, VERIFYING_KEY_FILE, WITNESS_FILE,
+};
+
+/// Inputs of a block as stored in `witness.bin`: (C*, β, γ, H(γ))
+type PairingWitness = (G1Affine, Fr, Fr, [u8; 32]);
+
+/// Prints the change from the SHA-256 baseline as an absolute and relative delta
+fn print_hash_deltas(hash: BlockIdHash, report: &BenchmarkReport, baseline: &BenchmarkReport) {
+    let percent = |new: f64, old: f64| (new - old) / old * 100.0;
+
+    println!("\n⚖️  {:?} vs Sha256:", hash);
+    println!("   🧮 Constraints: {} vs {} ({:+} / {:+.1}%)",
+             report.constraints, baseline.constraints,
+             report.constraints as i64 - baseline.constraints as i64,
+             percent(report.constraints as f64, baseline.constraints as f64));
+    println!("   🔧 Avg Setup Time:   {:.2} ms vs {:.2} ms ({:+.1} ms / {:+.1}%)",
+             report.setup.mean_ms, baseline.setup.mean_ms,
+             report.setup.mean_ms - baseline.setup.mean_ms, percent(report.setup.mean_ms, baseline.setup.mean_ms));
+    println!("   🎯 Avg Proving Time: {:.2} ms vs {:.2} ms ({:+.1} ms / {:+.1}%)",
+             report.prove.mean_ms, baseline.prove.mean_ms,
+             report.prove.mean_ms - baseline.prove.mean_ms, percent(report.prove.mean_ms, baseline.prove.mean_ms));
 }
 
 fn fq_to_bw6fr(fq: Fq) -> BW6Fr {
@@ -30,39 +44,123 @@ fn fq_to_bw6fr(fq: Fq) -> BW6Fr {
 }
 
 fn main() {
-    let mut rng = thread_rng();
-    let iterations = 10;
+    // An optional leading `setup`, `witness`, `prove` or `verify` works on the key files instead of benchmarking
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // `--iterations <n>`, `--seed <seed>` and `--out <dir>` may appear anywhere and configure the benchmark report
+    let options = BenchmarkOptions::from_args(&mut args);
+
+    let command = match args.first().map(String::as_str) {
+        Some("setup" | "witness" | "prove" | "verify") => Some(args.remove(0)),
+        _ => None,
+    };
+
+    // Select the γ hash with the first argument: `sha256` (default), `keccak` or `poseidon`
+    let hash = match args.first().map(String::as_str) {
+        None | Some("sha256") => BlockIdHash::Sha256,
+        Some("keccak") => BlockIdHash::Keccak256,
+        Some("poseidon") => BlockIdHash::Poseidon,
+        Some(other) => panic!("Unknown block ID hash `{}` (expected `sha256`, `keccak` or `poseidon`)", other),
+    };
+    println!("#️⃣  γ hash: {:?}", hash);
+
+    if let Some(command) = command {
+        return run_key_command(&command, hash);
+    }
 
-    use std::time::Duration;
-    let mut total_setup = Duration::ZERO;
-    let mut total_proving = Duration::ZERO;
-    let mut total_verifying = Duration::ZERO;
+    let report = run_benchmark(hash, &options);
+
+    // Other hashes are always reported against the SHA-256 baseline
+    if hash != BlockIdHash::Sha256 {
+        println!("\n🔁 Re-running with Sha256 for comparison");
+        let baseline = run_benchmark(BlockIdHash::Sha256, &options);
+        print_hash_deltas(hash, &report, &baseline);
+    }
+}
+
+/// Public inputs of `circuit`, mapped from the circuit field Fq into BW6-761's scalar field
+fn bw6_public_inputs(circuit: &PairingCircuit) -> Vec<BW6Fr> {
+    let cs = ConstraintSystem::<Fq>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    let instance_assignment = cs.borrow().unwrap().instance_assignment.clone();
+    instance_assignment.iter().skip(1).map(|fq_elem| fq_to_bw6fr(*fq_elem)).collect()
+}
 
-    println!("\n🚀 Running {} benchmark iterations...\n", iterations);
+/// `setup` writes fresh keys, `witness` writes the sample block, `prove` proves the block in
+/// the witness file with the stored proving key and `verify` checks the stored proof with the
+/// stored verifying key
+fn run_key_command(command: &str, hash: BlockIdHash) {
+    let dir = key_dir(&format!("pairing-{:?}", hash).to_lowercase());
+    let mut rng = thread_rng();
+    match command {
+        "setup" => {
+            let setup_start = Instant::now();
+            let (pk, vk) = Groth16::<BW6_761, LibsnarkReduction>::circuit_specific_setup(sample_circuit(hash), &mut rng).unwrap();
+            println!("🔧 Setup Time: {:.2?}", setup_start.elapsed());
+            save_keys(&dir, &pk, &vk).expect("Failed to write keys");
+            println!("🔑 Wrote {} and {} to {}", PROVING_KEY_FILE, VERIFYING_KEY_FILE, dir.display());
+        }
+        "witness" => {
+            let c = sample_circuit(hash);
+            let witness: PairingWitness = (c.c_star_affine, c.beta, c.gamma, c.gamma_hash);
+            save_witness(&dir, &witness).expect("Failed to write witness");
+            println!("🧾 Wrote the sample block to {} in {}", WITNESS_FILE, dir.display());
+        }
+        "prove" => {
+            let pk = load_proving_key::<BW6_761>(&dir).expect("Failed to read proving key; run `setup` first");
+            let (c_star_affine, beta, gamma, gamma_hash) = load_witness::<PairingWitness>(&dir).unwrap_or_else(|err| {
+                panic!("Failed to read {} in {} ({}); write the block to prove there first", WITNESS_FILE, dir.display(), err)
+            });
+            let circuit = PairingCircuit { c_star_affine, beta, gamma, gamma_hash, hash };
+
+            // Groth16 would still output a proof for a wrong witness, it just would not verify.
+            // The check's constraint system is dropped before proving, which builds its own.
+            let is_satisfied = {
+                let cs = ConstraintSystem::<Fq>::new_ref();
+                circuit.clone().generate_constraints(cs.clone()).expect("Failed to synthesize the circuit");
+                cs.is_satisfied().unwrap()
+            };
+            if !is_satisfied {
+                println!("❌ The block in {} does not satisfy the circuit", WITNESS_FILE);
+                std::process::exit(1);
+            }
+
+            let proving_start = Instant::now();
+            let proof = Groth16::<BW6_761, LibsnarkReduction>::prove(&pk, circuit.clone(), &mut rng).unwrap();
+            println!("🎯 Proving Time: {:.2?}", proving_start.elapsed());
+            save_proof(&dir, &proof, &bw6_public_inputs(&circuit)).expect("Failed to write proof");
+            println!("📄 Wrote {} and {} to {}", PROOF_FILE, PUBLIC_INPUTS_FILE, dir.display());
+        }
+        "verify" => {
+            let vk = load_verifying_key::<BW6_761>(&dir).expect("Failed to read verifying key; run `setup` first");
+            let (proof, public_inputs) = load_proof::<BW6_761>(&dir).expect("Failed to read proof; run `prove` first");
+            let verification_start = Instant::now();
+            let is_valid = Groth16::<BW6_761, LibsnarkReduction>::verify(&vk, &public_inputs, &proof).unwrap();
+            println!("🔍 Verification Time: {:.2?}", verification_start.elapsed());
+            if !is_valid {
+                println!("❌ Proof in {} is invalid", dir.display());
+                std::process::exit(1);
+            }
+            println!("✅ Proof in {} is valid", dir.display());
+        }
+        other => unreachable!("unknown key command `{}`", other),
+    }
+}
 
-    for i in 0..iterations {
-        println!("🔁 Iteration {}/{}", i + 1, iterations);
+/// Runs the benchmark iterations with the given γ hash, prints statistics and writes them
+/// to the report directory as `pairing-proof-pairing-<hash>.csv` and `.json`
+fn run_benchmark(hash: BlockIdHash, options: &BenchmarkOptions) -> BenchmarkReport {
+    let mut iterations = Vec::new();
 
-        // === Witness values ===
-        let beta = Fr::from(3u8);
-        let gamma = Fr::from(7u8);
-        let gamma_inv = gamma.inverse().unwrap();
-        let beta_over_gamma = beta * gamma_inv;
+    println!("\n🚀 Running {} benchmark iterations...\n", options.iterations);
 
-        // === Create public group element ===
-        let g1_gen = G1Affine::generator();
-        let c_star_affine = (g1_gen * beta_over_gamma).into_affine();
+    for i in 0..options.iterations {
+        println!("🔁 Iteration {}/{}", i + 1, options.iterations);
 
-        // === Compute hash ===
-        let gamma_hash = compute_gamma_hash(gamma);
+        let mut rng = StdRng::seed_from_u64(options.seed.wrapping_add(i as u64));
 
         // === Create circuit ===
-        let circuit = PairingCircuit {
-            c_star_affine,
-            beta,
-            gamma,
-            gamma_hash,
-        };
+        let circuit = sample_circuit(hash);
 
         // === Create constraint system and verify ===
         let cs = ConstraintSystem::<Fq>::new_ref();
@@ -73,33 +171,60 @@ fn main() {
         let setup_start = Instant::now();
         let (pk, vk) = Groth16::<BW6_761, LibsnarkReduction>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
         let setup_time = setup_start.elapsed();
-        total_setup += setup_time;
 
         // === Proving ===
         let proving_start = Instant::now();
         let proof = Groth16::<BW6_761, LibsnarkReduction>::prove(&pk, circuit.clone(), &mut rng).unwrap();
         let proving_time = proving_start.elapsed();
-        total_proving += proving_time;
 
         // === Extract public inputs ===
-        let instance_assignment = cs.borrow().unwrap().instance_assignment.clone();
-        let public_inputs: Vec<BW6Fr> = instance_assignment.iter().skip(1)
-            .map(|fq_elem| fq_to_bw6fr(*fq_elem)).collect();
+        let public_inputs = bw6_public_inputs(&circuit);
 
         // === Verification ===
         let verify_start = Instant::now();
         let is_valid = Groth16::<BW6_761, LibsnarkReduction>::verify(&vk, &public_inputs, &proof).unwrap();
         let verify_time = verify_start.elapsed();
-        total_verifying += verify_time;
 
         assert!(is_valid, "❌ Proof failed verification");
 
         println!("✅ Iteration {} passed (Setup: {:?}, Prove: {:?}, Verify: {:?})", i + 1, setup_time, proving_time, verify_time);
+
+        iterations.push(Iteration {
+            constraints: cs.num_constraints(),
+            witnesses: cs.num_witness_variables(),
+            proof_bytes: proof.compressed_size(),
+            setup: setup_time,
+            prove: proving_time,
+            verify: verify_time,
+        });
     }
 
-    // === Averages ===
-    println!("\n📊 Benchmark Summary ({} runs):", iterations);
-    println!("   🔧 Avg Setup Time:        {:.2?}", total_setup / iterations);
-    println!("   🎯 Avg Proving Time:      {:.2?}", total_proving / iterations);
-    println!("   🔍 Avg Verification Time: {:.2?}", total_verifying / iterations);
+    // === Results ===
+    let name = format!("pairing-{:?}", hash).to_lowercase();
+    let report = BenchmarkReport::new("pairing-proof", &name, options, &iterations);
+    report.print();
+    let (csv_path, json_path) = report.write(&options.out_dir).expect("Failed to write benchmark report");
+    println!("\n📝 Wrote {} and {}", csv_path.display(), json_path.display());
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use circuit_snapshot::{check_snapshot, CircuitCounts};
+
+    /// Counts for the pairing circuit, checked against `constraint-counts.snapshot`
+    #[test]
+    fn test_constraint_count_snapshot() {
+        let counts: Vec<CircuitCounts> = [
+            ("pairing_sha256", BlockIdHash::Sha256),
+            ("pairing_keccak256", BlockIdHash::Keccak256),
+            ("pairing_poseidon", BlockIdHash::Poseidon),
+        ]
+        .into_iter()
+        .map(|(name, hash)| CircuitCounts::of::<Fq, _>(name, sample_circuit(hash)))
+        .collect();
+
+        check_snapshot(concat!(env!("CARGO_MANIFEST_DIR"), "/constraint-counts.snapshot"), &counts);
+    }
 }